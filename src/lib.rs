@@ -0,0 +1,2133 @@
+//! The game logic of hangman, used by the `hangman` binary and usable by other programs
+//!
+//! A game is a [`Savefile`], guesses are applied to it with [`apply_guess`] or
+//! [`guess_characters`] and [`update_status`] checks if it has been won or lost.
+//! The game is shown with [`render_game`] or its parts like [`render_mask`] and
+//! [`render_gallows`], decorated through [`Paint`], and [`render_summary`] once it is over.
+//! New words are picked with [`pick_word`], [`category_wordlist`] or a [`WordSource`]
+
+use anyhow::{bail, Context};
+use chrono::{DateTime, Local};
+use figment::value::{Dict, Map, Value};
+use figment::{
+    providers::{Format, Json, Toml},
+    Error, Figment, Profile, Provider,
+};
+use log::{debug, info, warn};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Restricts the length of the words picked for a new game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// Words of 3 to 5 letters
+    Easy,
+    /// Words of 6 to 8 letters
+    Medium,
+    /// Words of 9 or more letters
+    Hard,
+}
+
+impl Difficulty {
+    /// Checks if the word's length fits this difficulty
+    pub fn accepts(&self, word: &str) -> bool {
+        let length = letter_count(word);
+        match self {
+            Difficulty::Easy => (3..=5).contains(&length),
+            Difficulty::Medium => (6..=8).contains(&length),
+            Difficulty::Hard => length >= 9,
+        }
+    }
+
+    /// Picks the difficulty for the next game from the results of the recent ones
+    ///
+    /// Starting from medium, every win moves it one step up and every loss one
+    /// step down, so no history gives medium
+    pub fn adaptive(recent: &[bool]) -> Difficulty {
+        let levels = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+        let level = recent.iter().fold(1, |level: usize, won| {
+            if *won {
+                (level + 1).min(levels.len() - 1)
+            } else {
+                level.saturating_sub(1)
+            }
+        });
+        levels[level]
+    }
+}
+
+/// The case the word and the alphabet are shown in, the stored word is never changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayCase {
+    /// Show the letters as they are in the word
+    #[default]
+    Original,
+    Lower,
+    Upper,
+}
+
+impl DisplayCase {
+    pub fn apply(&self, c: char) -> String {
+        match self {
+            DisplayCase::Original => c.to_string(),
+            DisplayCase::Lower => c.to_lowercase().collect(),
+            DisplayCase::Upper => c.to_uppercase().collect(),
+        }
+    }
+}
+
+/// How the masked word is shown
+#[derive(Debug, Clone, Copy)]
+pub struct MaskStyle {
+    /// The character shown for unguessed letters
+    pub mask_char: char,
+    pub case: DisplayCase,
+}
+
+/// One or more wordlists, so the config can take both a path and an array of paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Wordlists {
+    One(PathBuf),
+    Many(Vec<PathBuf>),
+}
+
+impl Wordlists {
+    pub fn paths(&self) -> &[PathBuf] {
+        match self {
+            Wordlists::One(path) => std::slice::from_ref(path),
+            Wordlists::Many(paths) => paths,
+        }
+    }
+}
+
+/// The most strikes a game can have, one for every letter of the alphabet
+pub const MAX_STRIKES: u8 = 26;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// The wordlist, or list of wordlists, to pick the word from when no source is given
+    pub wordlist: Option<Wordlists>,
+    /// A directory of `<category>.txt` wordlists
+    pub categories: Option<PathBuf>,
+    pub savefile: Option<PathBuf>,
+    pub logfile: Option<PathBuf>,
+    /// The size in bytes after which the logfile is rotated to `<logfile>.1`, 0 to never rotate
+    pub log_max_bytes: u64,
+    pub statsfile: Option<PathBuf>,
+    pub leaderboard: Option<PathBuf>,
+    /// Words fetched ahead of time from the API, see `cache refill`
+    pub word_cache: Option<PathBuf>,
+    /// A hosted plain text wordlist, one word per line, used when no local wordlist is configured
+    pub wordlist_url: Option<String>,
+    /// Where the wordlist of `wordlist_url` is kept once downloaded
+    pub wordlist_url_cache: Option<PathBuf>,
+    pub strikes: u8,
+    pub difficulty: Option<Difficulty>,
+    /// The most letters a picked word may have, words of any length are used if unset
+    pub max_word_length: Option<usize>,
+    /// The character shown for unguessed letters
+    pub mask_char: char,
+    pub display_case: DisplayCase,
+    /// Always show how many of the letters of the word have been found, see `show --progress`
+    pub show_progress: bool,
+    /// Mark correct and incorrect guesses with ✓ and ✗, so they can be told apart without color
+    pub use_symbols: bool,
+    pub case_insensitive: bool,
+    /// Let guessing a letter reveal its accented versions, like e for é
+    pub strip_accents: bool,
+    /// The format the savefile is written in, the default savefile takes its extension
+    pub format: SaveFormat,
+    /// Never use the word API, so no network requests are made
+    pub offline_only: bool,
+    pub word_api_url: String,
+    pub word_api_format: ApiFormat,
+    pub word_api_timeout_secs: u64,
+    /// The score a won game starts from
+    pub score_base: u32,
+    /// Points subtracted from the score for every incorrect guess
+    pub score_incorrect_penalty: u32,
+    /// Points subtracted from the score for every hint used
+    pub score_hint_penalty: u32,
+    /// Points added to the score for every strike left
+    pub score_strike_bonus: u32,
+    /// Warn after an incorrect guess once this many strikes or fewer are left
+    pub warn_at_strikes: u8,
+    /// Strikes lost for guessing the wrong word with `guess --word`
+    pub word_guess_penalty: u8,
+    /// The language of the picked words, one of en, es, fr or de
+    pub language: String,
+    /// Let repeated guesses cost a strike, see `guess --strict`
+    pub strict: bool,
+    /// Warn when a wordlist given to `new` has fewer usable words than this
+    pub min_wordlist_words: usize,
+    /// Hints per game that cost no strike, see `hint`
+    pub free_hints: u8,
+    /// Fill in the rest of the word one letter at a time when a game is lost, see `guess --animate`
+    pub animate_loss: bool,
+    /// The delay between the letters filled in when a game is lost
+    pub animate_delay_ms: u64,
+    /// Back up the savefile before changing the game, see `--backup`
+    pub backup: bool,
+    /// Show the apostrophes and hyphens of the word instead of making them guessable
+    pub reveal_punctuation: bool,
+}
+
+/// The directory used for the default file locations when the home directory is unknown
+///
+/// The warning is only logged the first time, as the directory is looked up for every default file
+fn fallback_config_dir(reason: &str) -> PathBuf {
+    static WARNED: Once = Once::new();
+    let dir = std::env::temp_dir().join("hangman");
+    WARNED.call_once(|| warn!("{}, storing the game files in {:?} instead", reason, dir));
+    dir
+}
+
+/// The directory the game files are stored in by default
+pub fn config_dir() -> PathBuf {
+    if cfg!(windows) {
+        match std::env::var("APPDATA").or_else(|_| std::env::var("HOMEPATH")) {
+            Ok(home) => PathBuf::from(home),
+            Err(_) => fallback_config_dir("Neither APPDATA nor HOMEPATH is set"),
+        }
+    } else {
+        match std::env::var("XDG_CONFIG_HOME") {
+            Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => match std::env::var("HOME") {
+                Ok(home) if !home.is_empty() => PathBuf::from(home).join(".config"),
+                _ => fallback_config_dir("HOME is not set"),
+            },
+        }
+    }
+}
+
+/// The configuration file of a profile selected with HANGMAN_PROFILE
+pub fn profile_path(profile: &str) -> PathBuf {
+    config_dir()
+        .join("hangman_profiles")
+        .join(format!("{}.toml", profile))
+}
+
+impl Default for Config {
+    /// Builds the default configuration, placing the files in the user's config directory
+    //noinspection SpellCheckingInspection
+    fn default() -> Self {
+        let (savefile, logfile, statsfile, leaderboard, word_cache, wordlist_url_cache) =
+            if cfg!(windows) {
+                let home = config_dir();
+                (
+                    home.join(".hangman-internal-savefile.toml"),
+                    home.join(".hangman.log"),
+                    home.join(".hangman-stats.toml"),
+                    home.join(".hangman-leaderboard.toml"),
+                    home.join(".hangman-word-cache.txt"),
+                    home.join(".hangman-wordlist-url-cache.txt"),
+                )
+            } else {
+                let config_dir = config_dir();
+                (
+                    config_dir.join("hangman_current_game.toml"),
+                    config_dir.join("hangman.log"),
+                    config_dir.join("hangman_stats.toml"),
+                    config_dir.join("hangman_leaderboard.toml"),
+                    config_dir.join("hangman_word_cache.txt"),
+                    config_dir.join("hangman_wordlist_url_cache.txt"),
+                )
+            };
+        Config {
+            wordlist: None,
+            categories: None,
+            savefile: Some(savefile),
+            logfile: Some(logfile),
+            log_max_bytes: 1024 * 1024,
+            statsfile: Some(statsfile),
+            leaderboard: Some(leaderboard),
+            word_cache: Some(word_cache),
+            wordlist_url: None,
+            wordlist_url_cache: Some(wordlist_url_cache),
+            strikes: 8,
+            difficulty: None,
+            max_word_length: None,
+            mask_char: '_',
+            display_case: DisplayCase::Original,
+            show_progress: false,
+            use_symbols: false,
+            case_insensitive: true,
+            strip_accents: false,
+            format: SaveFormat::Toml,
+            offline_only: false,
+            word_api_url: "https://random-word-api.vercel.app/api?words=1".to_string(),
+            word_api_format: ApiFormat::JsonArray,
+            word_api_timeout_secs: 5,
+            score_base: 100,
+            score_incorrect_penalty: 10,
+            score_hint_penalty: 15,
+            score_strike_bonus: 5,
+            warn_at_strikes: 2,
+            word_guess_penalty: 1,
+            language: DEFAULT_LANGUAGE.to_string(),
+            strict: false,
+            min_wordlist_words: 10,
+            free_hints: 0,
+            animate_loss: false,
+            animate_delay_ms: 250,
+            backup: false,
+            reveal_punctuation: true,
+        }
+    }
+}
+
+impl Config {
+    /// How guesses are compared to the word with this configuration
+    pub fn matching(&self) -> Matching {
+        Matching {
+            case_insensitive: self.case_insensitive,
+            strip_accents: self.strip_accents,
+            reveal_punctuation: self.reveal_punctuation,
+        }
+    }
+
+    /// How the masked word is shown with this configuration
+    pub fn mask_style(&self) -> MaskStyle {
+        MaskStyle {
+            mask_char: self.mask_char,
+            case: self.display_case,
+        }
+    }
+
+    /// How won games are scored with this configuration
+    pub fn scoring(&self) -> Scoring {
+        Scoring {
+            base: self.score_base,
+            incorrect_penalty: self.score_incorrect_penalty,
+            hint_penalty: self.score_hint_penalty,
+            strike_bonus: self.score_strike_bonus,
+        }
+    }
+}
+
+impl Provider for Config {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Default config")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let mut dict = Dict::new();
+        if let Some(wordlist) = &self.wordlist {
+            dict.insert("wordlist".to_string(), Value::serialize(wordlist)?);
+        }
+        if let Some(categories) = &self.categories {
+            dict.insert("categories".to_string(), Value::serialize(categories)?);
+        }
+        if let Some(savefile) = &self.savefile {
+            dict.insert("savefile".to_string(), Value::serialize(savefile)?);
+        }
+        if let Some(logfile) = &self.logfile {
+            dict.insert("logfile".to_string(), Value::serialize(logfile)?);
+        }
+        dict.insert("log_max_bytes".to_string(), Value::from(self.log_max_bytes));
+        if let Some(statsfile) = &self.statsfile {
+            dict.insert("statsfile".to_string(), Value::serialize(statsfile)?);
+        }
+        if let Some(leaderboard) = &self.leaderboard {
+            dict.insert("leaderboard".to_string(), Value::serialize(leaderboard)?);
+        }
+        if let Some(word_cache) = &self.word_cache {
+            dict.insert("word_cache".to_string(), Value::serialize(word_cache)?);
+        }
+        if let Some(wordlist_url) = &self.wordlist_url {
+            dict.insert(
+                "wordlist_url".to_string(),
+                Value::from(wordlist_url.clone()),
+            );
+        }
+        if let Some(wordlist_url_cache) = &self.wordlist_url_cache {
+            dict.insert(
+                "wordlist_url_cache".to_string(),
+                Value::serialize(wordlist_url_cache)?,
+            );
+        }
+        dict.insert("strikes".to_string(), Value::from(self.strikes));
+        if let Some(difficulty) = self.difficulty {
+            dict.insert("difficulty".to_string(), Value::serialize(difficulty)?);
+        }
+        if let Some(max_word_length) = self.max_word_length {
+            dict.insert(
+                "max_word_length".to_string(),
+                Value::serialize(max_word_length)?,
+            );
+        }
+        dict.insert("mask_char".to_string(), Value::from(self.mask_char));
+        dict.insert(
+            "display_case".to_string(),
+            Value::serialize(self.display_case)?,
+        );
+        dict.insert("show_progress".to_string(), Value::from(self.show_progress));
+        dict.insert("use_symbols".to_string(), Value::from(self.use_symbols));
+        dict.insert(
+            "case_insensitive".to_string(),
+            Value::from(self.case_insensitive),
+        );
+        dict.insert("strip_accents".to_string(), Value::from(self.strip_accents));
+        dict.insert("format".to_string(), Value::serialize(self.format)?);
+        dict.insert("offline_only".to_string(), Value::from(self.offline_only));
+        dict.insert(
+            "word_api_url".to_string(),
+            Value::from(self.word_api_url.clone()),
+        );
+        dict.insert(
+            "word_api_format".to_string(),
+            Value::serialize(self.word_api_format)?,
+        );
+        dict.insert(
+            "word_api_timeout_secs".to_string(),
+            Value::from(self.word_api_timeout_secs),
+        );
+        dict.insert("score_base".to_string(), Value::from(self.score_base));
+        dict.insert(
+            "score_incorrect_penalty".to_string(),
+            Value::from(self.score_incorrect_penalty),
+        );
+        dict.insert(
+            "score_hint_penalty".to_string(),
+            Value::from(self.score_hint_penalty),
+        );
+        dict.insert(
+            "score_strike_bonus".to_string(),
+            Value::from(self.score_strike_bonus),
+        );
+        dict.insert(
+            "warn_at_strikes".to_string(),
+            Value::from(self.warn_at_strikes),
+        );
+        dict.insert(
+            "word_guess_penalty".to_string(),
+            Value::from(self.word_guess_penalty),
+        );
+        dict.insert("language".to_string(), Value::from(self.language.clone()));
+        dict.insert("strict".to_string(), Value::from(self.strict));
+        dict.insert(
+            "min_wordlist_words".to_string(),
+            Value::from(self.min_wordlist_words),
+        );
+        dict.insert("free_hints".to_string(), Value::from(self.free_hints));
+        dict.insert("animate_loss".to_string(), Value::from(self.animate_loss));
+        dict.insert(
+            "animate_delay_ms".to_string(),
+            Value::from(self.animate_delay_ms),
+        );
+        dict.insert("backup".to_string(), Value::from(self.backup));
+        dict.insert(
+            "reveal_punctuation".to_string(),
+            Value::from(self.reveal_punctuation),
+        );
+        Ok(figment::value::Map::from([(Profile::Default, dict)]))
+    }
+}
+
+/// The state of the game stored in the savefile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GameStatus {
+    #[default]
+    InProgress,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Savefile {
+    pub word: String,
+    pub guessed: Vec<char>,
+    pub correct: Vec<char>,
+    pub incorrect: Vec<char>,
+    pub strikes_left: u8,
+    /// The amount of strikes the game started with
+    #[serde(default = "default_strikes")]
+    pub strikes: u8,
+    #[serde(default)]
+    pub status: GameStatus,
+    /// Every guessed character in order, with when it was guessed and if it was correct.
+    /// A repeat that cost a strike in strict mode is kept as an incorrect guess
+    #[serde(default)]
+    pub history: Vec<(DateTime<Local>, char, bool)>,
+    #[serde(default)]
+    pub hints_used: u8,
+    /// The name of the player, see `new --name`
+    #[serde(default)]
+    pub player: Option<String>,
+    /// Characters the player marked as absent with `guess --eliminate`. They are
+    /// only a note for the player, they cost no strikes and aren't guessed
+    #[serde(default)]
+    pub eliminated: Vec<char>,
+    /// When the game was started, unknown for savefiles from before it was stored
+    #[serde(default)]
+    pub started_at: Option<DateTime<Local>>,
+    /// When the game was won or lost
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Local>>,
+    /// The players taking turns on the word, see `new --players`
+    #[serde(default)]
+    pub players: Vec<Player>,
+    /// The index of the player whose turn it is
+    #[serde(default)]
+    pub turn: usize,
+}
+
+/// A player of a pass-and-play game, with the guesses they made
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Player {
+    pub name: String,
+    pub correct: u32,
+    pub incorrect: u32,
+}
+
+/// Savefiles from before the strikes were stored always started with 8
+fn default_strikes() -> u8 {
+    8
+}
+
+impl Savefile {
+    /// Checks if a game has been started, the default savefile has no word
+    pub fn is_active(&self) -> bool {
+        !self.word.is_empty()
+    }
+
+    /// Fails unless a game has been started
+    pub fn ensure_active(&self) -> anyhow::Result<()> {
+        if !self.is_active() {
+            bail!("No game is active, start one with `hangman new`");
+        }
+        Ok(())
+    }
+
+    /// Fails unless a game has been started and is neither won nor lost yet
+    pub fn ensure_in_progress(&self) -> anyhow::Result<()> {
+        self.ensure_active()?;
+        if self.status != GameStatus::InProgress {
+            bail!("The current game is already over, start a new one with `new`");
+        }
+        Ok(())
+    }
+
+    /// Checks if a character of the word should be shown to the player
+    pub fn is_revealed(&self, c: char, matching: Matching) -> bool {
+        if self.status == GameStatus::Won || matching.reveals(c) || self.correct.contains(&c) {
+            return true;
+        }
+        let folded = matching.fold(&c.to_string());
+        self.correct
+            .iter()
+            .any(|g| matching.fold(&g.to_string()) == folded)
+    }
+
+    /// The player whose turn it is, if several players take turns
+    pub fn current_player(&self) -> Option<&Player> {
+        self.players.get(self.turn % self.players.len().max(1))
+    }
+
+    /// Credits the guess to the player whose turn it is, and passes the turn on
+    pub fn record_turn(&mut self, correct: bool) {
+        let count = self.players.len();
+        if count == 0 {
+            return;
+        }
+        let player = &mut self.players[self.turn % count];
+        if correct {
+            player.correct += 1;
+        } else {
+            player.incorrect += 1;
+        }
+        self.turn = (self.turn + 1) % count;
+    }
+
+    /// Takes back the guess credited by `record_turn` to the previous player,
+    /// and gives the turn back to them
+    ///
+    /// Nothing changes when the previous player has no such guess credited, as
+    /// guesses made outside of `play` don't pass the turn on
+    pub fn undo_turn(&mut self, correct: bool) {
+        let count = self.players.len();
+        if count == 0 {
+            return;
+        }
+        let previous = (self.turn + count - 1) % count;
+        let player = &mut self.players[previous];
+        let credited = if correct {
+            &mut player.correct
+        } else {
+            &mut player.incorrect
+        };
+        if *credited > 0 {
+            *credited -= 1;
+            self.turn = previous;
+        }
+    }
+
+    /// The time spent on the game, up to now if it is still in progress
+    pub fn elapsed(&self) -> Option<chrono::Duration> {
+        self.started_at
+            .map(|started_at| self.finished_at.unwrap_or_else(Local::now) - started_at)
+    }
+
+    /// Counts the guesses of the history that repeated a character guessed
+    /// before, each of them cost a strike in strict mode
+    ///
+    /// A repeat is an entry whose character is earlier in the history, or an
+    /// incorrect entry of a correct character, which was revealed by a hint
+    pub fn repeated_guesses(&self) -> usize {
+        self.history
+            .iter()
+            .enumerate()
+            .filter(|(index, (_, character, correct))| self.is_repeat(*index, *character, *correct))
+            .count()
+    }
+
+    fn is_repeat(&self, index: usize, character: char, correct: bool) -> bool {
+        self.history[..index]
+            .iter()
+            .any(|(_, c, _)| *c == character)
+            || (!correct && self.correct.contains(&character))
+    }
+
+    /// Reverts the last guess of the history, giving back the strike it cost and
+    /// the turn of the player who made it
+    ///
+    /// Returns the character and if it was correct, `None` if nothing was guessed
+    pub fn undo_guess(&mut self) -> Option<(char, bool)> {
+        let index = self.history.len().checked_sub(1)?;
+        let (_, character, correct) = self.history[index];
+        if !self.is_repeat(index, character, correct) {
+            self.guessed.retain(|c| *c != character);
+            if correct {
+                self.correct.retain(|c| *c != character);
+            } else {
+                self.incorrect.retain(|c| *c != character);
+            }
+        }
+        if !correct {
+            self.strikes_left = (self.strikes_left + 1).min(self.strikes);
+        }
+        self.history.pop();
+        self.undo_turn(correct);
+        Some((character, correct))
+    }
+
+    /// Checks if every letter of the word has been guessed
+    pub fn is_solved(&self, matching: Matching) -> bool {
+        self.word.chars().all(|c| self.is_revealed(c, matching))
+    }
+
+    /// Checks that the guesses and strikes agree with the word, as a hand-edited
+    /// savefile could contradict itself
+    ///
+    /// Correct characters are compared ignoring case and accents, incorrect ones
+    /// exactly, so the check holds for every matching configuration
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(1..=MAX_STRIKES).contains(&self.strikes) {
+            bail!(
+                "It has {} strikes, it must have between 1 and {}",
+                self.strikes,
+                MAX_STRIKES
+            );
+        }
+        if self.strikes_left > self.strikes {
+            bail!(
+                "It has {} strikes left, more than the {} strikes it started with",
+                self.strikes_left,
+                self.strikes
+            );
+        }
+
+        let lenient = Matching {
+            case_insensitive: true,
+            strip_accents: true,
+            reveal_punctuation: true,
+        };
+        let word = lenient.fold(&self.word);
+        for c in &self.correct {
+            if !word.contains(&lenient.fold(&c.to_string())) {
+                bail!("'{}' is marked as correct but isn't in the word", c);
+            }
+        }
+        for c in &self.incorrect {
+            if self.word.contains(*c) {
+                bail!("'{}' is marked as incorrect but is in the word", c);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Savefile {
+    fn default() -> Self {
+        Self {
+            word: "".to_string(),
+            guessed: vec![],
+            correct: vec![],
+            incorrect: vec![],
+            strikes_left: 8,
+            strikes: 8,
+            status: GameStatus::InProgress,
+            history: vec![],
+            hints_used: 0,
+            player: None,
+            eliminated: vec![],
+            started_at: None,
+            finished_at: None,
+            players: vec![],
+            turn: 0,
+        }
+    }
+}
+
+impl Provider for Savefile {
+    fn metadata(&self) -> figment::Metadata {
+        figment::Metadata::named("Default savefile")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let mut dict = Dict::new();
+        dict.insert("word".to_string(), Value::from(self.word.clone()));
+        dict.insert("guessed".to_string(), Value::from(self.guessed.clone()));
+        dict.insert("correct".to_string(), Value::from(self.correct.clone()));
+        dict.insert("incorrect".to_string(), Value::from(self.incorrect.clone()));
+        dict.insert("strikes_left".to_string(), Value::from(self.strikes_left));
+        dict.insert("strikes".to_string(), Value::from(self.strikes));
+        dict.insert("status".to_string(), Value::serialize(self.status)?);
+        dict.insert("history".to_string(), Value::serialize(&self.history)?);
+        dict.insert("hints_used".to_string(), Value::from(self.hints_used));
+        if let Some(player) = &self.player {
+            dict.insert("player".to_string(), Value::from(player.clone()));
+        }
+        dict.insert(
+            "eliminated".to_string(),
+            Value::from(self.eliminated.clone()),
+        );
+        if let Some(started_at) = self.started_at {
+            dict.insert("started_at".to_string(), Value::serialize(started_at)?);
+        }
+        if let Some(finished_at) = self.finished_at {
+            dict.insert("finished_at".to_string(), Value::serialize(finished_at)?);
+        }
+        dict.insert("players".to_string(), Value::serialize(&self.players)?);
+        dict.insert("turn".to_string(), Value::serialize(self.turn)?);
+        Ok(figment::value::Map::from([(Profile::Default, dict)]))
+    }
+}
+
+/// The file format used to store the savefile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SaveFormat {
+    #[default]
+    Toml,
+    Json,
+}
+
+impl SaveFormat {
+    /// Detects the format from the file's extension, ignoring its case
+    pub fn from_path(path: &Path) -> Option<SaveFormat> {
+        let extension = path.extension()?.to_str()?;
+        [SaveFormat::Toml, SaveFormat::Json]
+            .into_iter()
+            .find(|format| extension.eq_ignore_ascii_case(format.extension()))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SaveFormat::Toml => "toml",
+            SaveFormat::Json => "json",
+        }
+    }
+}
+
+pub fn load_savefile(savefile_path: &Path, format: SaveFormat) -> anyhow::Result<Savefile> {
+    let figment = match format {
+        SaveFormat::Toml => Figment::new().merge(Toml::file(savefile_path)),
+        SaveFormat::Json => Figment::new().merge(Json::file(savefile_path)),
+    };
+    let savefile: Savefile = figment
+        .extract()
+        .with_context(|| format!("Failed to load savefile {:?}", savefile_path))?;
+    savefile
+        .validate()
+        .with_context(|| format!("Savefile {:?} is invalid", savefile_path))?;
+    Ok(savefile)
+}
+
+/// Writes the file through a temporary file in the same directory that is renamed
+/// into place, so a crash mid-write never leaves a truncated file behind
+pub fn write_atomic(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    let mut file = std::fs::File::create(&temporary)
+        .with_context(|| format!("Failed to open {:?}", temporary))?;
+    file.write_all(contents)
+        .and_then(|_| file.sync_all())
+        .with_context(|| format!("Failed to write {:?}", temporary))?;
+    std::fs::rename(&temporary, path)
+        .with_context(|| format!("Failed to move {:?} to {:?}", temporary, path))
+}
+
+pub fn write_toml(path: &Path, value: &impl Serialize) -> anyhow::Result<()> {
+    let contents =
+        toml::to_string(value).with_context(|| format!("Failed to serialize {:?}", path))?;
+    write_atomic(path, contents.as_bytes())
+}
+
+pub fn write_savefile(
+    savefile_path: &Path,
+    savefile: &Savefile,
+    format: SaveFormat,
+) -> anyhow::Result<()> {
+    match format {
+        SaveFormat::Toml => write_toml(savefile_path, savefile),
+        SaveFormat::Json => {
+            let contents = serde_json::to_vec_pretty(savefile)
+                .with_context(|| format!("Failed to serialize {:?}", savefile_path))?;
+            write_atomic(savefile_path, &contents)
+        }
+    }
+}
+
+/// Where the current game is loaded from and saved to
+pub trait SaveStore {
+    fn load(&self) -> anyhow::Result<Savefile>;
+    fn save(&mut self, savefile: &Savefile) -> anyhow::Result<()>;
+}
+
+/// Keeps the current game in the savefile on disk
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    pub path: PathBuf,
+    pub format: SaveFormat,
+}
+
+impl SaveStore for FileStore {
+    fn load(&self) -> anyhow::Result<Savefile> {
+        load_savefile(&self.path, self.format)
+    }
+
+    fn save(&mut self, savefile: &Savefile) -> anyhow::Result<()> {
+        write_savefile(&self.path, savefile, self.format)
+    }
+}
+
+/// Keeps the current game in memory only, for running the handlers without touching the disk
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    pub savefile: Savefile,
+}
+
+impl SaveStore for MemoryStore {
+    fn load(&self) -> anyhow::Result<Savefile> {
+        self.savefile.validate().context("Savefile is invalid")?;
+        Ok(self.savefile.clone())
+    }
+
+    fn save(&mut self, savefile: &Savefile) -> anyhow::Result<()> {
+        self.savefile = savefile.clone();
+        Ok(())
+    }
+}
+
+/// The statistics of every finished game
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub games_won: u32,
+    pub games_lost: u32,
+    pub total_guesses: u32,
+    /// The sum of the strikes left on every won game, used for the average
+    pub strikes_left_on_wins: u32,
+    /// The sum of the scores of every won game
+    pub total_score: u32,
+    pub best_score: u32,
+    /// Whether each of the most recent games was won, oldest first, see `new --adaptive`
+    pub recent: Vec<bool>,
+}
+
+/// How many of the most recent results are kept in the statistics
+pub const RECENT_GAMES: usize = 10;
+
+impl Stats {
+    /// Adds a finished game to the statistics, the score only counts for won games
+    pub fn record(&mut self, savefile: &Savefile, score: u32) {
+        match savefile.status {
+            GameStatus::InProgress => return,
+            GameStatus::Won => {
+                self.games_won += 1;
+                self.strikes_left_on_wins += savefile.strikes_left as u32;
+                self.total_score += score;
+                self.best_score = self.best_score.max(score);
+            }
+            GameStatus::Lost => self.games_lost += 1,
+        }
+        self.total_guesses += savefile.guessed.len() as u32;
+        self.recent.push(savefile.status == GameStatus::Won);
+        if self.recent.len() > RECENT_GAMES {
+            self.recent.remove(0);
+        }
+    }
+
+    pub fn average_strikes_left_on_wins(&self) -> f64 {
+        if self.games_won == 0 {
+            0.0
+        } else {
+            self.strikes_left_on_wins as f64 / self.games_won as f64
+        }
+    }
+
+    pub fn average_score(&self) -> f64 {
+        if self.games_won == 0 {
+            0.0
+        } else {
+            self.total_score as f64 / self.games_won as f64
+        }
+    }
+}
+
+/// The most games kept on the leaderboard
+pub const LEADERBOARD_SIZE: usize = 100;
+
+/// A won game on the leaderboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: Option<String>,
+    pub word: String,
+    pub score: u32,
+    pub strikes_left: u8,
+    pub date: DateTime<Local>,
+}
+
+/// The best won games, sorted from the highest score
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    /// Adds a won game, ties are broken by the strikes left, dropping the worst
+    /// games once the leaderboard is full
+    pub fn record(&mut self, savefile: &Savefile, score: u32) {
+        if savefile.status != GameStatus::Won {
+            return;
+        }
+        self.entries.push(LeaderboardEntry {
+            name: savefile.player.clone(),
+            word: savefile.word.clone(),
+            score,
+            strikes_left: savefile.strikes_left,
+            date: Local::now(),
+        });
+        self.entries.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.strikes_left.cmp(&a.strikes_left))
+        });
+        self.entries.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+/// Loads the leaderboard, a missing file means no games were won yet
+pub fn load_leaderboard(leaderboard_path: &Path) -> anyhow::Result<Leaderboard> {
+    Figment::new()
+        .merge(Toml::file(leaderboard_path))
+        .extract()
+        .with_context(|| format!("Failed to load leaderboard {:?}", leaderboard_path))
+}
+
+pub fn write_leaderboard(leaderboard_path: &Path, leaderboard: &Leaderboard) -> anyhow::Result<()> {
+    if let Some(parent) = leaderboard_path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create leaderboard directory")?;
+    }
+    write_toml(leaderboard_path, leaderboard)
+}
+
+/// The points used to score a won game
+#[derive(Debug, Clone, Copy)]
+pub struct Scoring {
+    pub base: u32,
+    pub incorrect_penalty: u32,
+    pub hint_penalty: u32,
+    pub strike_bonus: u32,
+}
+
+impl Scoring {
+    /// Scores a game, the score never drops below zero
+    ///
+    /// Repeated guesses that cost a strike count as incorrect guesses
+    pub fn score(&self, savefile: &Savefile) -> u32 {
+        let incorrect = savefile.incorrect.len() + savefile.repeated_guesses();
+        self.base
+            .saturating_sub(incorrect as u32 * self.incorrect_penalty)
+            .saturating_sub(savefile.hints_used as u32 * self.hint_penalty)
+            .saturating_add(savefile.strikes_left as u32 * self.strike_bonus)
+    }
+}
+
+/// Loads the statistics, a missing statsfile means no games were finished yet
+pub fn load_stats(statsfile_path: &Path) -> anyhow::Result<Stats> {
+    Figment::new()
+        .merge(Toml::file(statsfile_path))
+        .extract()
+        .with_context(|| format!("Failed to load statsfile {:?}", statsfile_path))
+}
+
+pub fn write_stats(statsfile_path: &Path, stats: &Stats) -> anyhow::Result<()> {
+    if let Some(parent) = statsfile_path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create statsfile directory")?;
+    }
+    write_toml(statsfile_path, stats)
+}
+
+/// How guessed characters are compared to the word
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matching {
+    pub case_insensitive: bool,
+    pub strip_accents: bool,
+    /// Whether the punctuation of the word is shown rather than guessed
+    pub reveal_punctuation: bool,
+}
+
+/// The punctuation a word may have besides letters and spaces
+pub const WORD_PUNCTUATION: [char; 3] = ['\'', '’', '-'];
+
+impl Matching {
+    /// Checks if the character of the word is always shown, never needing to be guessed
+    pub fn reveals(&self, c: char) -> bool {
+        c == ' ' || (self.reveal_punctuation && WORD_PUNCTUATION.contains(&c))
+    }
+
+    /// Normalizes the text so equal letters compare equal, composing accents
+    /// into single characters and optionally lowercasing and stripping them
+    pub fn fold(&self, text: &str) -> String {
+        let mut text = text.nfc().collect::<String>();
+        if self.case_insensitive {
+            text = text.to_lowercase();
+        }
+        if self.strip_accents {
+            text = text
+                .nfd()
+                .filter(|c| !is_combining_mark(*c))
+                .nfc()
+                .collect();
+        }
+        text
+    }
+}
+
+/// Splits the guess into normalized characters, leaving out whitespace
+pub fn normalize_guess(guess: &str, matching: Matching) -> Vec<char> {
+    matching
+        .fold(guess)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+/// Applies a single, already normalized, guessed character to the game
+///
+/// Returns false if the character had already been guessed
+pub fn apply_guess(savefile: &mut Savefile, character: char, matching: Matching) -> bool {
+    if savefile.guessed.contains(&character) {
+        info!(
+            "Character '{}' has already been guessed, skipping",
+            character
+        );
+        return false;
+    }
+    let word = matching.fold(&savefile.word);
+    let correct = word.contains(character);
+
+    savefile.guessed.push(character);
+    savefile.history.push((Local::now(), character, correct));
+    if correct {
+        info!("Character '{}' is in the word", character);
+        savefile.correct.push(character);
+    } else {
+        info!("Character '{}' is not in the word", character);
+        savefile.incorrect.push(character);
+        savefile.strikes_left = savefile.strikes_left.saturating_sub(1);
+    }
+    true
+}
+
+/// Splits the characters of a guess into the ones to apply, each once, and the
+/// ones skipped because they were guessed before or repeated in the guess
+pub fn unique_guesses(savefile: &Savefile, characters: Vec<char>) -> (Vec<char>, Vec<char>) {
+    let mut unique = vec![];
+    let mut skipped = vec![];
+    for character in characters {
+        if savefile.guessed.contains(&character) || unique.contains(&character) {
+            if !skipped.contains(&character) {
+                skipped.push(character);
+            }
+        } else {
+            unique.push(character);
+        }
+    }
+    (unique, skipped)
+}
+
+/// The characters of a guess that weren't applied as new guesses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuessOutcome {
+    /// The characters skipped because they were guessed before or repeated in the guess
+    pub skipped: Vec<char>,
+    /// The characters that cost a strike for being guessed again, with `strict` set
+    pub penalized: Vec<char>,
+}
+
+/// Applies a list of guessed characters to the game until it is over, updating its status
+///
+/// Characters already guessed are skipped, unless `strict` is set, in which
+/// case each of them costs a strike, including one repeated within the guess
+pub fn guess_characters(
+    savefile: &mut Savefile,
+    guess: &str,
+    strict: bool,
+    matching: Matching,
+) -> GuessOutcome {
+    let mut outcome = GuessOutcome::default();
+    let characters = if strict {
+        normalize_guess(guess, matching)
+    } else {
+        let (characters, skipped) = unique_guesses(savefile, normalize_guess(guess, matching));
+        outcome.skipped = skipped;
+        characters
+    };
+    for character in characters {
+        if savefile.guessed.contains(&character) {
+            penalize_repeat(savefile, character);
+            outcome.penalized.push(character);
+        } else {
+            apply_guess(savefile, character, matching);
+        }
+        if update_status(savefile, matching) {
+            break;
+        }
+    }
+    outcome
+}
+
+/// Charges a strike for guessing a character again, see `guess --strict`
+///
+/// The repeat is kept in the history as an incorrect guess, so it can be undone and replayed
+pub fn penalize_repeat(savefile: &mut Savefile, character: char) {
+    info!(
+        "Character '{}' has already been guessed, costing a strike",
+        character
+    );
+    savefile.history.push((Local::now(), character, false));
+    savefile.strikes_left = savefile.strikes_left.saturating_sub(1);
+}
+
+/// Guesses the whole word at once, winning the game if it is right and costing
+/// `penalty` strikes if not
+///
+/// Returns true if the word is right
+pub fn guess_word(savefile: &mut Savefile, word: &str, penalty: u8, matching: Matching) -> bool {
+    if matching.fold(&collapse_spaces(word)) == matching.fold(&savefile.word) {
+        info!("'{}' is the word", word);
+        savefile.status = GameStatus::Won;
+        savefile.finished_at = Some(Local::now());
+        true
+    } else {
+        info!("'{}' is not the word", word);
+        savefile.strikes_left = savefile.strikes_left.saturating_sub(penalty);
+        update_status(savefile, matching);
+        false
+    }
+}
+
+/// Checks if the game has been won or lost, marking when it finished
+///
+/// Returns true if the game is over
+pub fn update_status(savefile: &mut Savefile, matching: Matching) -> bool {
+    if savefile.is_solved(matching) {
+        savefile.status = GameStatus::Won;
+    } else if savefile.strikes_left == 0 {
+        savefile.status = GameStatus::Lost;
+    }
+    let over = savefile.status != GameStatus::InProgress;
+    if over {
+        savefile.finished_at = Some(Local::now());
+    }
+    over
+}
+
+/// Decorates the guesses shown by the renderers, like with colors on a terminal
+///
+/// Both methods leave the text as it is by default
+pub trait Paint {
+    /// Decorates a correct or incorrect guess
+    fn color(&self, text: &str, _correct: bool) -> String {
+        text.to_string()
+    }
+    /// Decorates a guess listed among others, where a symbol may tell correct and
+    /// incorrect guesses apart
+    fn mark(&self, text: &str, correct: bool) -> String {
+        self.color(text, correct)
+    }
+}
+
+/// Renders the guesses without any decoration
+pub struct Plain;
+
+impl Paint for Plain {}
+
+/// Renders the word with every unguessed letter replaced by the mask character.
+/// Spaces are always shown as they are, and so is punctuation if `reveal_punctuation` is set
+///
+/// The characters are joined with `separator`, and every revealed letter is
+/// passed through `paint`, so callers can decorate it
+pub fn render_mask_with(
+    savefile: &Savefile,
+    matching: Matching,
+    style: MaskStyle,
+    separator: &str,
+    paint: impl Fn(String) -> String,
+) -> String {
+    savefile
+        .word
+        .chars()
+        .map(|c| {
+            if matching.reveals(c) {
+                c.to_string()
+            } else if savefile.is_revealed(c, matching) {
+                paint(style.case.apply(c))
+            } else {
+                style.mask_char.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+/// Renders the masked word without any decoration, with the characters separated by spaces
+pub fn render_mask(savefile: &Savefile, matching: Matching, style: MaskStyle) -> String {
+    render_mask_with(savefile, matching, style, " ", |letter| letter)
+}
+
+/// Renders the game on one line, like `_p_le | strikes 6/8 | guessed: a,p,l`
+pub fn render_compact(savefile: &Savefile, matching: Matching, style: MaskStyle) -> String {
+    format!(
+        "{} | strikes {}/{} | guessed: {}",
+        render_mask_with(savefile, matching, style, "", |letter| letter),
+        savefile.strikes_left,
+        savefile.strikes,
+        savefile
+            .guessed
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    )
+}
+
+/// The number of body parts drawn on a complete gallows
+const GALLOWS_STAGES: u8 = 8;
+
+/// Renders the ASCII gallows for the given amount of strikes left
+///
+/// The strikes used are scaled to the 8 drawing stages, so configurations
+/// with more or less strikes still end on a complete hangman
+pub fn render_gallows(strikes_left: u8, strikes_total: u8) -> String {
+    let used = strikes_total.saturating_sub(strikes_left) as u32;
+    let stage = if strikes_total == 0 {
+        GALLOWS_STAGES as u32
+    } else {
+        (used * GALLOWS_STAGES as u32).div_ceil(strikes_total as u32)
+    };
+    let part = |n: u32, c: char| if stage >= n { c } else { ' ' };
+
+    [
+        "  +---+".to_string(),
+        "  |   |".to_string(),
+        format!("  {}   |", part(1, 'O')),
+        format!(" {}{}{}  |", part(3, '/'), part(2, '|'), part(4, '\\')),
+        format!(" {} {}  |", part(5, '/'), part(6, '\\')),
+        format!("{}   {} |", part(7, '_'), part(8, '_')),
+        "      |".to_string(),
+        "=========".to_string(),
+    ]
+    .join("\n")
+}
+
+/// Renders the totals of a game, shown once it is over
+pub fn render_summary(savefile: &Savefile) -> String {
+    let mut lines = vec![
+        format!("Word: {}", savefile.word),
+        format!("Guesses made: {}", savefile.guessed.len()),
+        format!("Correct guesses: {}", savefile.correct.len()),
+        format!("Incorrect guesses: {}", savefile.incorrect.len()),
+        format!(
+            "Strikes used: {}/{}",
+            savefile.strikes.saturating_sub(savefile.strikes_left),
+            savefile.strikes
+        ),
+        format!("Strikes left: {}", savefile.strikes_left),
+        format!("Hints used: {}", savefile.hints_used),
+    ];
+    if let Some(elapsed) = savefile.elapsed() {
+        lines.push(format!("Time: {}", format_elapsed(elapsed)));
+    }
+    if !savefile.players.is_empty() {
+        lines.push(render_scoreboard(&savefile.players));
+    }
+    lines.join("\n")
+}
+
+/// Renders the guesses of every player, and who found the most letters
+pub fn render_scoreboard(players: &[Player]) -> String {
+    let mut lines = players
+        .iter()
+        .map(|player| {
+            format!(
+                "{}: {} correct, {} incorrect",
+                player.name, player.correct, player.incorrect
+            )
+        })
+        .collect::<Vec<String>>();
+    let most = players
+        .iter()
+        .map(|player| player.correct)
+        .max()
+        .unwrap_or(0);
+    if most > 0 {
+        let leaders = players
+            .iter()
+            .filter(|player| player.correct == most)
+            .map(|player| player.name.as_str())
+            .collect::<Vec<&str>>();
+        lines.push(format!("Most letters found: {}", leaders.join(" and ")));
+    }
+    lines.join("\n")
+}
+
+/// Formats a duration like `1h 02m 03s`, leaving out the hours and minutes when they are zero
+pub fn format_elapsed(elapsed: chrono::Duration) -> String {
+    let seconds = elapsed.num_seconds().max(0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// Renders how many of the unique letters of the word have been found
+pub fn render_progress(savefile: &Savefile, matching: Matching) -> String {
+    let mut letters = matching
+        .fold(&savefile.word)
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .collect::<Vec<char>>();
+    letters.sort_unstable();
+    letters.dedup();
+    let found = letters
+        .iter()
+        .filter(|c| savefile.correct.contains(c))
+        .count();
+    let percent = if letters.is_empty() {
+        100
+    } else {
+        found * 100 / letters.len()
+    };
+    format!(
+        "{}/{} unique letters found ({}%)",
+        found,
+        letters.len(),
+        percent
+    )
+}
+
+/// Renders the letters a to z, with the already guessed ones in brackets
+///
+/// Guessed or eliminated letters outside of a to z, like the ü, ß or é of the
+/// bundled languages, are added after them
+pub fn render_alphabet(savefile: &Savefile, case: DisplayCase, paint: &dyn Paint) -> String {
+    let mut others = savefile
+        .guessed
+        .iter()
+        .chain(&savefile.eliminated)
+        .copied()
+        .filter(|c| !c.is_ascii_lowercase())
+        .collect::<Vec<char>>();
+    others.sort_unstable();
+    others.dedup();
+    ('a'..='z')
+        .chain(others)
+        .map(|c| {
+            let letter = case.apply(c);
+            if savefile.guessed.contains(&c) {
+                paint.mark(&format!("[{}]", letter), savefile.correct.contains(&c))
+            } else if savefile.eliminated.contains(&c) {
+                format!("({})", letter)
+            } else {
+                letter
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Renders the gallows, the masked word and the strikes of the game
+pub fn render_game(
+    savefile: &Savefile,
+    matching: Matching,
+    style: MaskStyle,
+    alphabet: bool,
+    paint: &dyn Paint,
+) -> String {
+    let mut lines = vec![
+        render_gallows(savefile.strikes_left, savefile.strikes),
+        render_mask_with(savefile, matching, style, " ", |letter| {
+            paint.color(&letter, true)
+        }),
+    ];
+    if alphabet {
+        lines.push(render_alphabet(savefile, style.case, paint));
+    }
+    lines.push(format!("Strikes left: {}", savefile.strikes_left));
+    lines.push(format!(
+        "Incorrect guesses: {}",
+        savefile
+            .incorrect
+            .iter()
+            .map(|c| paint.mark(&c.to_string(), false))
+            .collect::<Vec<String>>()
+            .join(", ")
+    ));
+    lines.join("\n")
+}
+
+/// Renders how many times each correctly guessed letter appears in the word
+pub fn render_frequencies(savefile: &Savefile, matching: Matching, paint: &dyn Paint) -> String {
+    let word = matching.fold(&savefile.word);
+    savefile
+        .correct
+        .iter()
+        .map(|c| {
+            let count = word.chars().filter(|w| w == c).count();
+            let times = if count == 1 { "time" } else { "times" };
+            format!(
+                "{} appears {} {}",
+                paint.color(&c.to_string(), true),
+                count,
+                times
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Counts the letters of a word or phrase, leaving out the spaces
+pub fn letter_count(word: &str) -> usize {
+    word.chars().filter(|c| c.is_alphabetic()).count()
+}
+
+/// Checks if a wordlist line can be used as a word or phrase, skipping blank
+/// lines, `#` comments and anything but letters, spaces, apostrophes and hyphens
+pub fn is_usable_word(line: &str) -> bool {
+    !line.starts_with('#')
+        && line.chars().any(|c| c.is_alphabetic())
+        && line
+            .chars()
+            .all(|c| c.is_alphabetic() || c == ' ' || WORD_PUNCTUATION.contains(&c))
+}
+
+/// Collapses the whitespace of a phrase into single spaces
+pub fn collapse_spaces(phrase: &str) -> String {
+    phrase.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Gets every usable word accepted by the filter from a wordlist
+pub fn usable_words(wordlist: &str, accepts: impl Fn(&str) -> bool) -> Vec<String> {
+    let wordlist = wordlist.nfc().collect::<String>();
+    wordlist
+        .lines()
+        .map(collapse_spaces)
+        .filter(|s| is_usable_word(s) && accepts(s))
+        .collect()
+}
+
+/// Trims a word or phrase chosen by the player and checks that it only contains
+/// letters, spaces, apostrophes and hyphens
+pub fn validate_word(word: &str) -> anyhow::Result<String> {
+    let word = collapse_spaces(&word.nfc().collect::<String>());
+    if !is_usable_word(&word) {
+        bail!("The given word must only contain letters, spaces, apostrophes and hyphens");
+    }
+    Ok(word)
+}
+
+/// The language used when the requested one isn't bundled
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The wordlists used when the API can't be reached, by language
+const OFFLINE_WORDLISTS: [(&str, &str); 4] = [
+    ("en", include_str!("wordlists/en.txt")),
+    ("es", include_str!("wordlists/es.txt")),
+    ("fr", include_str!("wordlists/fr.txt")),
+    ("de", include_str!("wordlists/de.txt")),
+];
+
+/// Gets the bundled language matching the requested one, falling back to English
+pub fn bundled_language(language: &str) -> &'static str {
+    let language = language.trim().to_lowercase();
+    match OFFLINE_WORDLISTS.iter().find(|(name, _)| *name == language) {
+        Some((name, _)) => name,
+        None => {
+            warn!(
+                "Language {} is not bundled, falling back to {}",
+                language, DEFAULT_LANGUAGE
+            );
+            DEFAULT_LANGUAGE
+        }
+    }
+}
+
+/// Gets the offline wordlist of a bundled language
+pub fn offline_wordlist(language: &str) -> &'static str {
+    OFFLINE_WORDLISTS
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(OFFLINE_WORDLISTS[0].1, |(_, wordlist)| wordlist)
+}
+
+/// Picks a random usable word accepted by the filter from a wordlist
+pub fn pick_word(
+    wordlist: &str,
+    accepts: impl Fn(&str) -> bool,
+    rng: &mut impl Rng,
+) -> Option<String> {
+    usable_words(wordlist, accepts).choose(rng).cloned()
+}
+
+/// The categories compiled into the binary
+const BUNDLED_CATEGORIES: [(&str, &str); 3] = [
+    ("animals", include_str!("categories/animals.txt")),
+    ("countries", include_str!("categories/countries.txt")),
+    ("programming", include_str!("categories/programming.txt")),
+];
+
+/// Gets the wordlist of a category
+///
+/// A `<category>.txt` file in the configured categories directory takes
+/// precedence over the bundled categories
+pub fn category_wordlist(category: &str, categories_dir: Option<&Path>) -> anyhow::Result<String> {
+    if let Some(directory) = categories_dir {
+        let path = directory.join(format!("{}.txt", category));
+        if path.is_file() {
+            return std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read category wordlist {:?}", path));
+        }
+    }
+    BUNDLED_CATEGORIES
+        .iter()
+        .find(|(name, _)| *name == category)
+        .map(|(_, wordlist)| wordlist.to_string())
+        .with_context(|| {
+            format!(
+                "Category {} does not exist, use `categories` to list them",
+                category
+            )
+        })
+}
+
+/// Lists the bundled categories and the ones in the categories directory
+pub fn available_categories(categories_dir: Option<&Path>) -> anyhow::Result<Vec<String>> {
+    let mut categories = BUNDLED_CATEGORIES
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<String>>();
+    if let Some(directory) = categories_dir.filter(|d| d.is_dir()) {
+        for entry in std::fs::read_dir(directory)
+            .with_context(|| format!("Failed to read categories directory {:?}", directory))?
+        {
+            let path = entry?.path();
+            if path.is_file() && path.extension() == Some("txt".as_ref()) {
+                if let Some(name) = path.file_stem() {
+                    categories.push(name.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    categories.sort();
+    categories.dedup();
+    Ok(categories)
+}
+
+/// Takes the first usable word accepted by the filter out of the word cache, see `cache refill`
+///
+/// The word is left in the cache when `remove` is false
+pub fn take_cached_word(
+    cache_path: &Path,
+    accepts: impl Fn(&str) -> bool,
+    remove: bool,
+) -> anyhow::Result<Option<String>> {
+    if !cache_path.exists() {
+        return Ok(None);
+    }
+    let cache = std::fs::read_to_string(cache_path)
+        .with_context(|| format!("Failed to read word cache {:?}", cache_path))?;
+    let mut words = cache.lines().collect::<Vec<&str>>();
+    let Some((index, word)) = words.iter().enumerate().find_map(|(index, line)| {
+        validate_word(line)
+            .ok()
+            .filter(|word| accepts(word))
+            .map(|word| (index, word))
+    }) else {
+        debug!("The word cache has no usable words matching the difficulty and length");
+        return Ok(None);
+    };
+    if remove {
+        words.remove(index);
+        let mut contents = words.join("\n");
+        if !contents.is_empty() {
+            contents.push('\n');
+        }
+        write_atomic(cache_path, contents.as_bytes())?;
+    }
+    Ok(Some(word))
+}
+
+/// Where `new` gets the word from when no wordlist, category or cached word is used
+pub trait WordSource {
+    /// Gets a word in the language accepted by the filter, `None` if there is none
+    fn fetch_word(&self, language: &str, accepts: &dyn Fn(&str) -> bool) -> Option<String>;
+    /// Describes where the words come from, for reporting the source of the word
+    fn describe(&self) -> String;
+    /// Checks if the source keeps `new` off the network, so no hosted wordlist is downloaded either
+    fn offline(&self) -> bool {
+        false
+    }
+}
+
+/// Gets the first accepted word of the offline wordlist, without using the network
+pub struct OfflineWordSource;
+
+impl WordSource for OfflineWordSource {
+    fn fetch_word(&self, language: &str, accepts: &dyn Fn(&str) -> bool) -> Option<String> {
+        usable_words(offline_wordlist(language), accepts)
+            .into_iter()
+            .next()
+    }
+
+    fn describe(&self) -> String {
+        "the offline wordlist, without the network".to_string()
+    }
+
+    fn offline(&self) -> bool {
+        true
+    }
+}
+
+/// How the response of the word API is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiFormat {
+    /// A JSON array of words, like `["word"]`, the first one is used
+    JsonArray,
+    /// The word as plain text, the first line is used
+    PlainText,
+}
+
+impl ApiFormat {
+    pub fn parse(&self, text: &str) -> Option<String> {
+        self.parse_all(text).into_iter().next()
+    }
+
+    /// Parses every word of the response, for when a batch of words was asked for
+    pub fn parse_all(&self, text: &str) -> Vec<String> {
+        match self {
+            ApiFormat::JsonArray => serde_json::from_str::<Vec<String>>(text).unwrap_or_default(),
+            ApiFormat::PlainText => text
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Comparing guesses like the default configuration
+    const MATCHING: Matching = Matching {
+        case_insensitive: true,
+        strip_accents: false,
+        reveal_punctuation: true,
+    };
+
+    /// A game of the word that was just started
+    fn game(word: &str) -> Savefile {
+        Savefile {
+            word: word.to_string(),
+            ..Savefile::default()
+        }
+    }
+
+    /// Comparing guesses exactly as typed
+    const CASE_SENSITIVE: Matching = Matching {
+        case_insensitive: false,
+        ..MATCHING
+    };
+
+    /// The default style, showing the letters as they are in the word
+    const STYLE: MaskStyle = MaskStyle {
+        mask_char: '_',
+        case: DisplayCase::Original,
+    };
+
+    #[test]
+    fn fold_lowercases_only_when_case_insensitive() {
+        assert_eq!(MATCHING.fold("McDonald"), "mcdonald");
+        assert_eq!(CASE_SENSITIVE.fold("McDonald"), "McDonald");
+    }
+
+    #[test]
+    fn case_insensitive_guesses_match_either_case() {
+        let mut savefile = game("McDonald");
+        for character in normalize_guess("MD", MATCHING) {
+            assert!(apply_guess(&mut savefile, character, MATCHING));
+        }
+        assert_eq!(savefile.correct, vec!['m', 'd']);
+        assert!(savefile.incorrect.is_empty());
+        assert!(savefile.is_revealed('M', MATCHING));
+        assert!(savefile.is_revealed('D', MATCHING));
+        assert!(savefile.is_revealed('d', MATCHING));
+        assert!(!savefile.is_revealed('c', MATCHING));
+        assert_eq!(render_mask(&savefile, MATCHING, STYLE), "M _ D _ _ _ _ d");
+        assert_eq!(savefile.word, "McDonald");
+    }
+
+    #[test]
+    fn case_sensitive_guesses_only_match_the_same_case() {
+        let mut savefile = game("McDonald");
+        for character in normalize_guess("md", CASE_SENSITIVE) {
+            apply_guess(&mut savefile, character, CASE_SENSITIVE);
+        }
+        assert_eq!(savefile.correct, vec!['d']);
+        assert_eq!(savefile.incorrect, vec!['m']);
+        assert!(!savefile.is_revealed('M', CASE_SENSITIVE));
+        assert!(!savefile.is_revealed('D', CASE_SENSITIVE));
+        assert!(savefile.is_revealed('d', CASE_SENSITIVE));
+        assert_eq!(savefile.strikes_left, 7);
+    }
+
+    /// Letting guessing a letter reveal its accented versions
+    const STRIP_ACCENTS: Matching = Matching {
+        strip_accents: true,
+        ..MATCHING
+    };
+
+    #[test]
+    fn fold_composes_accents_and_strips_them_when_asked() {
+        let decomposed = "nai\u{308}ve";
+        assert_eq!(MATCHING.fold(decomposed), "naïve");
+        assert_eq!(MATCHING.fold("naïve"), "naïve");
+        assert_eq!(STRIP_ACCENTS.fold(decomposed), "naive");
+        assert_eq!(STRIP_ACCENTS.fold("Jalapeño"), "jalapeno");
+        assert_eq!(normalize_guess("n\u{303}", MATCHING), vec!['ñ']);
+    }
+
+    #[test]
+    fn accented_letters_need_the_accent_unless_stripped() {
+        let mut savefile = game("jalapeño");
+        apply_guess(&mut savefile, 'n', MATCHING);
+        assert_eq!(savefile.incorrect, vec!['n']);
+        assert!(!savefile.is_revealed('ñ', MATCHING));
+
+        let [accented] = normalize_guess("n\u{303}", MATCHING)[..] else {
+            panic!("the decomposed guess should be a single character");
+        };
+        apply_guess(&mut savefile, accented, MATCHING);
+        assert_eq!(savefile.correct, vec!['ñ']);
+        assert!(savefile.is_revealed('ñ', MATCHING));
+
+        let mut savefile = game("jalapeño");
+        apply_guess(&mut savefile, 'n', STRIP_ACCENTS);
+        assert_eq!(savefile.correct, vec!['n']);
+        assert!(savefile.is_revealed('ñ', STRIP_ACCENTS));
+    }
+
+    #[test]
+    fn decomposed_words_are_revealed_by_composed_guesses() {
+        assert_eq!(usable_words("nai\u{308}ve\n", |_| true), vec!["naïve"]);
+        assert_eq!(validate_word("jalapen\u{303}o").unwrap(), "jalapeño");
+
+        let mut savefile = game("nai\u{308}ve");
+        for character in normalize_guess("naïve", MATCHING) {
+            apply_guess(&mut savefile, character, MATCHING);
+        }
+        assert!(savefile.incorrect.is_empty());
+
+        let mut savefile = game("naïve");
+        for character in normalize_guess("naive", STRIP_ACCENTS) {
+            apply_guess(&mut savefile, character, STRIP_ACCENTS);
+        }
+        assert!(savefile.is_revealed('ï', STRIP_ACCENTS));
+        assert!(!savefile.is_revealed('ï', MATCHING));
+        assert!(update_status(&mut savefile, STRIP_ACCENTS));
+        assert_eq!(savefile.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn spaces_of_phrases_are_always_revealed() {
+        let mut savefile = game("new york");
+        assert_eq!(render_mask(&savefile, MATCHING, STYLE), "_ _ _   _ _ _ _");
+        guess_characters(&mut savefile, "newyor", false, MATCHING);
+        assert!(!savefile.is_solved(MATCHING));
+        assert_eq!(render_mask(&savefile, MATCHING, STYLE), "n e w   y o r _");
+        assert_eq!(savefile.status, GameStatus::InProgress);
+
+        guess_characters(&mut savefile, "k", false, MATCHING);
+        assert!(savefile.is_solved(MATCHING));
+        assert_eq!(savefile.status, GameStatus::Won);
+        assert!(!savefile.guessed.contains(&' '));
+    }
+
+    #[test]
+    fn phrases_are_trimmed_to_single_spaces() {
+        assert_eq!(collapse_spaces("  new \t york  "), "new york");
+        assert_eq!(validate_word(" new   york\n").unwrap(), "new york");
+        assert!(validate_word("new york 2").is_err());
+        assert!(validate_word("   ").is_err());
+        assert_eq!(
+            usable_words("new  york\nlos angeles\n# cities\n\n", |_| true),
+            vec!["new york", "los angeles"]
+        );
+    }
+
+    /// Making the punctuation of the word guessable
+    const GUESS_PUNCTUATION: Matching = Matching {
+        reveal_punctuation: false,
+        ..MATCHING
+    };
+
+    #[test]
+    fn punctuation_is_revealed_only_when_configured() {
+        for c in ['\'', '’', '-', ' '] {
+            assert!(MATCHING.reveals(c));
+        }
+        assert!(!GUESS_PUNCTUATION.reveals('\''));
+        assert!(!GUESS_PUNCTUATION.reveals('-'));
+        assert!(GUESS_PUNCTUATION.reveals(' '));
+        assert!(!MATCHING.reveals('a'));
+    }
+
+    #[test]
+    fn punctuation_is_left_out_of_the_win_check_when_revealed() {
+        let mut savefile = game("can't");
+        assert_eq!(render_mask(&savefile, MATCHING, STYLE), "_ _ _ ' _");
+        guess_characters(&mut savefile, "cant", false, MATCHING);
+        assert!(savefile.is_solved(MATCHING));
+
+        let mut savefile = game("mother-in-law");
+        guess_characters(&mut savefile, "motherinlaw", false, GUESS_PUNCTUATION);
+        assert!(!savefile.is_solved(GUESS_PUNCTUATION));
+        assert_eq!(savefile.status, GameStatus::InProgress);
+        guess_characters(&mut savefile, "-", false, GUESS_PUNCTUATION);
+        assert!(savefile.is_solved(GUESS_PUNCTUATION));
+        assert_eq!(savefile.status, GameStatus::Won);
+    }
+
+    #[test]
+    fn words_with_apostrophes_and_hyphens_are_usable() {
+        assert!(is_usable_word("can't"));
+        assert!(is_usable_word("mother-in-law"));
+        assert!(is_usable_word("rock ’n’ roll"));
+        assert!(!is_usable_word("--"));
+        assert!(!is_usable_word("can't!"));
+        assert!(!is_usable_word("# can't"));
+        assert_eq!(
+            usable_words("can't\nmother-in-law\nc3po\n", |_| true),
+            vec!["can't", "mother-in-law"]
+        );
+    }
+
+    #[test]
+    fn save_format_ignores_the_case_of_the_extension() {
+        assert_eq!(
+            SaveFormat::from_path(Path::new("s.TOML")),
+            Some(SaveFormat::Toml)
+        );
+        assert_eq!(
+            SaveFormat::from_path(Path::new("s.Json")),
+            Some(SaveFormat::Json)
+        );
+        assert_eq!(SaveFormat::from_path(Path::new("s.txt")), None);
+        assert_eq!(SaveFormat::from_path(Path::new("toml")), None);
+    }
+
+    #[test]
+    fn unique_guesses_skip_letters_guessed_before_or_repeated() {
+        let mut savefile = game("banana");
+        apply_guess(&mut savefile, 'a', MATCHING);
+
+        let (unique, skipped) = unique_guesses(&savefile, vec!['a', 'a', 'a']);
+        assert!(unique.is_empty());
+        assert_eq!(skipped, vec!['a']);
+        let (unique, skipped) = unique_guesses(&savefile, vec!['b', 'b', 'a', 'z']);
+        assert_eq!(unique, vec!['b', 'z']);
+        assert_eq!(skipped, vec!['b', 'a']);
+    }
+
+    #[test]
+    fn repeated_guesses_cost_no_strike() {
+        let mut savefile = game("banana");
+        guess_characters(&mut savefile, "a", false, MATCHING);
+        let outcome = guess_characters(&mut savefile, "aaa", false, MATCHING);
+        assert_eq!(outcome.skipped, vec!['a']);
+        assert!(outcome.penalized.is_empty());
+        assert_eq!(savefile.guessed, vec!['a']);
+        assert_eq!(savefile.history.len(), 1);
+        assert_eq!(savefile.strikes_left, savefile.strikes);
+
+        let outcome = guess_characters(&mut savefile, "zz", false, MATCHING);
+        assert_eq!(outcome.skipped, vec!['z']);
+        assert_eq!(savefile.strikes_left, savefile.strikes - 1);
+    }
+
+    #[test]
+    fn strict_repeats_are_recorded_and_undone() {
+        let mut savefile = game("hello");
+        guess_characters(&mut savefile, "h", false, MATCHING);
+        let outcome = guess_characters(&mut savefile, "hz", true, MATCHING);
+        assert_eq!(outcome.penalized, vec!['h']);
+        assert_eq!(savefile.strikes_left, 6);
+        assert_eq!(savefile.history.len(), 3);
+        assert_eq!(savefile.repeated_guesses(), 1);
+
+        assert_eq!(savefile.undo_guess(), Some(('z', false)));
+        assert_eq!(savefile.undo_guess(), Some(('h', false)));
+        assert_eq!(savefile.guessed, vec!['h']);
+        assert_eq!(savefile.correct, vec!['h']);
+        assert_eq!(savefile.strikes_left, 8);
+        assert_eq!(savefile.undo_guess(), Some(('h', true)));
+        assert!(savefile.guessed.is_empty());
+        assert_eq!(savefile.strikes_left, savefile.strikes);
+        assert_eq!(savefile.undo_guess(), None);
+    }
+
+    #[test]
+    fn strict_repeats_count_in_the_score() {
+        let scoring = Scoring {
+            base: 100,
+            incorrect_penalty: 10,
+            hint_penalty: 0,
+            strike_bonus: 0,
+        };
+        let mut savefile = game("hi");
+        guess_characters(&mut savefile, "hh", true, MATCHING);
+        assert_eq!(scoring.score(&savefile), 90);
+        guess_characters(&mut savefile, "i", true, MATCHING);
+        assert_eq!(savefile.status, GameStatus::Won);
+        assert_eq!(scoring.score(&savefile), 90);
+    }
+
+    /// Brackets correct guesses with `+` and incorrect ones with `-`
+    struct Signs;
+
+    impl Paint for Signs {
+        fn color(&self, text: &str, correct: bool) -> String {
+            let sign = if correct { '+' } else { '-' };
+            format!("{}{}{}", sign, text, sign)
+        }
+    }
+
+    #[test]
+    fn render_game_decorates_the_guesses_through_the_paint() {
+        let mut savefile = game("hi");
+        guess_characters(&mut savefile, "hz", false, MATCHING);
+        let plain = render_game(&savefile, MATCHING, STYLE, true, &Plain);
+        assert!(plain.contains("\nh _\n"));
+        assert!(plain.contains("[h] i"));
+        assert!(plain.ends_with("Incorrect guesses: z"));
+
+        let signed = render_game(&savefile, MATCHING, STYLE, true, &Signs);
+        assert!(signed.contains("\n+h+ _\n"));
+        assert!(signed.contains("+[h]+ i"));
+        assert!(signed.contains("-[z]-"));
+        assert!(signed.ends_with("Incorrect guesses: -z-"));
+        let mut hoho = game("hoho");
+        guess_characters(&mut hoho, "h", false, MATCHING);
+        assert_eq!(
+            render_frequencies(&hoho, MATCHING, &Signs),
+            "+h+ appears 2 times"
+        );
+    }
+
+    #[test]
+    fn words_are_picked_from_categories_and_seeded_rngs() {
+        let animals = category_wordlist("animals", None).unwrap();
+        assert!(category_wordlist("no such category", None).is_err());
+        assert!(available_categories(None)
+            .unwrap()
+            .contains(&"animals".to_string()));
+
+        let pick = |seed| pick_word(&animals, |_| true, &mut StdRng::seed_from_u64(seed));
+        assert_eq!(pick(7), pick(7));
+        assert!(usable_words(&animals, |_| true).contains(&pick(7).unwrap()));
+        assert_eq!(
+            pick_word(&animals, |_| false, &mut rand::thread_rng()),
+            None
+        );
+    }
+
+    /// The gallows of a drawing stage, as drawn for the default 8 strikes
+    fn stage(stage: u8) -> String {
+        render_gallows(GALLOWS_STAGES - stage, GALLOWS_STAGES)
+    }
+
+    #[test]
+    fn gallows_draw_a_body_part_per_strike_of_8() {
+        assert!(!stage(0).contains('O'));
+        assert!(stage(1).contains("  O   |"));
+        assert_eq!(stage(2).lines().nth(3), Some("  |   |"));
+        assert_eq!(stage(1).lines().nth(3), Some("      |"));
+        assert_eq!(
+            stage(8),
+            "  +---+\n  |   |\n  O   |\n /|\\  |\n / \\  |\n_   _ |\n      |\n========="
+        );
+        for used in 0..=8 {
+            assert_eq!(render_gallows(8 - used, 8), stage(used));
+        }
+    }
+
+    #[test]
+    fn gallows_scale_the_stages_to_the_strikes() {
+        // With 3 strikes every strike draws about 3 body parts, rounding up
+        assert_eq!(render_gallows(3, 3), stage(0));
+        assert_eq!(render_gallows(2, 3), stage(3));
+        assert_eq!(render_gallows(1, 3), stage(6));
+        assert_eq!(render_gallows(0, 3), stage(8));
+
+        // With 26 strikes the first strike already draws the head, and the
+        // stages change when the strikes used pass a multiple of 26 / 8
+        assert_eq!(render_gallows(26, 26), stage(0));
+        assert_eq!(render_gallows(25, 26), stage(1));
+        assert_eq!(render_gallows(23, 26), stage(1));
+        assert_eq!(render_gallows(22, 26), stage(2));
+        assert_eq!(render_gallows(4, 26), stage(7));
+        assert_eq!(render_gallows(3, 26), stage(8));
+        assert_eq!(render_gallows(0, 26), stage(8));
+    }
+
+    #[test]
+    fn summaries_of_won_and_lost_games() {
+        let started_at = Local::now() - chrono::Duration::seconds(90);
+        let mut won = Savefile {
+            started_at: Some(started_at),
+            hints_used: 1,
+            ..game("hi")
+        };
+        guess_characters(&mut won, "zhi", false, MATCHING);
+        won.finished_at = Some(started_at + chrono::Duration::seconds(83));
+        assert_eq!(won.status, GameStatus::Won);
+        let summary = render_summary(&won);
+        assert!(summary.starts_with("Word: hi\n"));
+        assert!(summary.contains("Guesses made: 3\nCorrect guesses: 2\nIncorrect guesses: 1\n"));
+        assert!(summary.contains("Strikes used: 1/8\nStrikes left: 7\n"));
+        assert!(summary.contains("Hints used: 1\n"));
+        assert!(summary.ends_with("Time: 1m 23s"));
+
+        let mut lost = Savefile {
+            strikes_left: 2,
+            strikes: 2,
+            ..game("hi")
+        };
+        guess_characters(&mut lost, "xyz", false, MATCHING);
+        assert_eq!(lost.status, GameStatus::Lost);
+        let summary = render_summary(&lost);
+        assert!(summary.starts_with("Word: hi\n"));
+        assert!(summary.contains("Guesses made: 2\n"));
+        assert!(summary.contains("Strikes used: 2/2\nStrikes left: 0\n"));
+        assert!(summary.ends_with("Hints used: 0"));
+    }
+
+    #[test]
+    fn undo_gives_the_turn_back_to_the_player_who_guessed() {
+        let player = |name: &str| Player {
+            name: name.to_string(),
+            ..Player::default()
+        };
+        let mut savefile = Savefile {
+            players: vec![player("alice"), player("bob")],
+            ..game("hi")
+        };
+        for character in ['h', 'z'] {
+            apply_guess(&mut savefile, character, MATCHING);
+            let correct = savefile.correct.contains(&character);
+            savefile.record_turn(correct);
+        }
+        assert_eq!(savefile.turn, 0);
+        assert_eq!(savefile.players[1].incorrect, 1);
+
+        assert_eq!(savefile.undo_guess(), Some(('z', false)));
+        assert_eq!(savefile.turn, 1);
+        assert_eq!(savefile.current_player().unwrap().name, "bob");
+        assert_eq!(savefile.players[1], player("bob"));
+        assert_eq!(savefile.players[0].correct, 1);
+
+        assert_eq!(savefile.undo_guess(), Some(('h', true)));
+        assert_eq!(savefile.turn, 0);
+        assert_eq!(savefile.players, vec![player("alice"), player("bob")]);
+    }
+
+    #[test]
+    fn blank_and_unusable_lines_of_the_word_cache_are_skipped() {
+        let cache = std::env::temp_dir().join(format!(
+            "hangman-test-word-cache-{}.txt",
+            std::process::id()
+        ));
+        std::fs::write(
+            &cache,
+            "\n   \n# fetched words\nR2D2\n  new   york \nkiwi\n",
+        )
+        .unwrap();
+
+        let taken = take_cached_word(&cache, |_| true, false).unwrap();
+        assert_eq!(taken.as_deref(), Some("new york"));
+        let taken = take_cached_word(&cache, |word| word == "kiwi", true).unwrap();
+        assert_eq!(taken.as_deref(), Some("kiwi"));
+        let taken = take_cached_word(&cache, |word| word == "kiwi", true).unwrap();
+        assert_eq!(taken, None);
+        assert_eq!(
+            std::fs::read_to_string(&cache).unwrap(),
+            "\n   \n# fetched words\nR2D2\n  new   york \n"
+        );
+        std::fs::remove_file(cache).unwrap();
+    }
+
+    #[test]
+    fn letters_beyond_a_to_z_are_added_to_the_alphabet() {
+        let mut savefile = game("süß");
+        savefile.eliminated.push('é');
+        guess_characters(&mut savefile, "ßüzä", false, MATCHING);
+        let alphabet = render_alphabet(&savefile, DisplayCase::Original, &Plain);
+        assert!(alphabet.starts_with("a b c"));
+        assert!(alphabet.ends_with("y [z] [ß] [ä] (é) [ü]"));
+        assert_eq!(alphabet.matches("[ü]").count(), 1);
+
+        let upper = render_alphabet(&savefile, DisplayCase::Upper, &Plain);
+        assert!(upper.ends_with("Y [Z] [SS] [Ä] (É) [Ü]"));
+    }
+}
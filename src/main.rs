@@ -1,31 +1,90 @@
-use chrono::Local;
-use clap::{Parser, Subcommand};
+use anyhow::{bail, Context};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{Generator, Shell};
 use fern::{
     colors::{Color, ColoredLevelConfig},
     Dispatch,
 };
-use figment::value::{Dict, Map, Value};
 use figment::{
     providers::{Format, Toml},
-    Error, Figment, Profile, Provider,
+    Figment,
 };
-use log::{debug, error, info};
-use rand::{thread_rng, Rng};
-use serde::{Deserialize, Serialize};
-use std::io::Write;
-use std::path::PathBuf;
+use flate2::read::GzDecoder;
+use hangman_rs::{
+    apply_guess, available_categories, bundled_language, category_wordlist, collapse_spaces,
+    config_dir, format_elapsed, guess_characters, guess_word, is_usable_word, letter_count,
+    load_leaderboard, load_savefile, load_stats, normalize_guess, offline_wordlist,
+    penalize_repeat, pick_word, profile_path, render_compact, render_frequencies, render_game,
+    render_mask_with, render_progress, render_summary, take_cached_word, update_status,
+    usable_words, validate_word, write_atomic, write_leaderboard, write_stats, Config, Difficulty,
+    DisplayCase, FileStore, GameStatus, MaskStyle, Matching, OfflineWordSource, Paint, Player,
+    SaveFormat, SaveStore, Savefile, Scoring, WordSource, DEFAULT_LANGUAGE, MAX_STRIKES,
+};
+use log::{debug, error, info, warn};
+use rand::{rngs::StdRng, seq::SliceRandom, thread_rng, SeedableRng};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use unicode_normalization::UnicodeNormalization;
 
 #[derive(Parser, Clone)]
-#[command(version, author, about)]
+#[command(version, author, about, after_help = EXIT_CODES_HELP)]
 struct Cli {
     /// The debug level to use, default is 0, meaning errors only. Max is 3
     #[arg(short, long, default_value = "0")]
     debug: u8,
 
-    /// The configuration file to use, if any. Uses HANGMAN_CONFIG by default, if not provided uses ~/.config/hangman.toml
+    /// The configuration file to use, if any
+    ///
+    /// Without it, the profile named by HANGMAN_PROFILE is used from the
+    /// `hangman_profiles` directory in the config directory, then the file
+    /// named by HANGMAN_CONFIG, then the default configuration.
+    ///
+    /// HANGMAN_PROFILE picks a whole file, while --profile picks a section within
+    /// the file in use, so `HANGMAN_PROFILE=family hangman --profile kids` reads the
+    /// `[profile.kids]` section of `hangman_profiles/family.toml`
     #[arg(short, long)]
     config: Option<PathBuf>,
 
+    /// Merge the `[profile.<PROFILE>]` section of the configuration file over the rest of it
+    ///
+    /// The section is looked up in the file chosen by --config, HANGMAN_PROFILE or
+    /// HANGMAN_CONFIG, it doesn't select a file of `hangman_profiles` like HANGMAN_PROFILE
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Don't color the game output, also disabled when NO_COLOR is set
+    #[arg(long)]
+    no_color: bool,
+
+    /// Print the game as JSON for `show`, `query` and `guess` instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Only print the essential game state, and only log errors regardless of --debug
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Never create the savefile, commands that change the current game fail when it is
+    /// missing. Commands that only read the current game never create it
+    #[arg(long)]
+    no_create: bool,
+
+    /// Never ask the word API for a word, `new` takes the first matching word of the
+    /// offline wordlist instead, so games can be predicted in tests
+    #[arg(long)]
+    no_network: bool,
+
+    /// Copy the savefile to `<savefile>.bak` before a command changes the current game,
+    /// replacing the previous backup. Also enabled by the `backup` config
+    #[arg(long)]
+    backup: bool,
+
     #[command(subcommand)]
     subcommands: Commands,
 }
@@ -33,258 +92,2074 @@ struct Cli {
 #[derive(Subcommand, Debug, Clone)]
 enum Commands {
     /// Guess a list of characters
+    #[command(
+        after_help = "Examples:\n  hangman guess --guess aeiou\n  hangman guess --eliminate --guess xyz\n  hangman guess --word hangman\n  hangman guess --file guesses.txt"
+    )]
     Guess {
         /// The list of characters to check
-        #[arg(short, long)]
-        guess: String,
+        #[arg(short, long, required_unless_present_any = ["word", "file"])]
+        guess: Option<String>,
+        /// Apply the guesses in the file in order, one list of characters per line
+        #[arg(short, long, value_name = "FILE", conflicts_with_all = ["guess", "word", "eliminate"])]
+        file: Option<PathBuf>,
+        /// Guess the whole word at once, winning the game if it is right and costing
+        /// the configured `word_guess_penalty` strikes if not
+        #[arg(short, long, conflicts_with_all = ["guess", "eliminate"])]
+        word: Option<String>,
+        /// Only mark the characters as known to be absent, without guessing them
+        #[arg(long)]
+        eliminate: bool,
+        /// Let every character that was already guessed cost a strike instead of skipping it,
+        /// including one repeated within the same guess. Also enabled by the `strict` config
+        #[arg(long, conflicts_with = "eliminate")]
+        strict: bool,
+        /// Fill in the rest of the word one letter at a time if the game is lost. Also
+        /// enabled by the `animate_loss` config, with the delay set by `animate_delay_ms`
+        #[arg(long, conflicts_with = "eliminate")]
+        animate: bool,
     },
     /// Query to see if a character has already been guessed
     ///
     /// If no character is provided, the entire list of guessed
     /// characters will be returned with a status if it is correct
+    #[command(after_help = "Examples:\n  hangman query --check e\n  hangman query --count")]
     Query {
         ///The list of characters to query
         #[arg(short, long)]
         check: Option<String>,
+        /// Also print how many guesses were made, when no characters are given
+        #[arg(long, conflicts_with = "check")]
+        count: bool,
     },
     /// Start a new game
     ///
     /// Optionally, provide a filename to select a random word from
     /// If no filename is provided, a random word will be selected
     /// From a wordlist online
-    New {
-        #[arg(short, long, value_name = "OPTIONAL FILE")]
-        file: Option<PathBuf>,
-    },
+    #[command(
+        after_help = "Examples:\n  hangman new --category animals --difficulty easy\n  hangman new --word - --strikes 6"
+    )]
+    New(NewArgs),
     /// Save the current game from the program's internal file to a custom file
+    ///
+    /// Instead of a file, a named slot managed by the program can be used
+    #[command(after_help = "Example:\n  hangman save --slot evening")]
     Save {
-        #[arg(short, long, value_name = "FILE")]
-        file: PathBuf,
+        #[arg(short, long, value_name = "FILE", required_unless_present = "slot")]
+        file: Option<PathBuf>,
+        /// The name of the slot to save to
+        #[arg(long, conflicts_with = "file")]
+        slot: Option<String>,
+        /// Overwrite the file without asking if it already exists
+        #[arg(long)]
+        force: bool,
     },
     /// Load a game from a file to program's internal file
+    ///
+    /// Instead of a file, a named slot managed by the program can be used
+    #[command(
+        after_help = "Examples:\n  hangman load --slot evening\n  hangman load --verify --file shared.toml"
+    )]
     Load {
-        #[arg(short, long, value_name = "FILE")]
-        file: PathBuf,
+        #[arg(short, long, value_name = "FILE", required_unless_present = "slot")]
+        file: Option<PathBuf>,
+        /// The name of the slot to load from
+        #[arg(long, conflicts_with = "file")]
+        slot: Option<String>,
+        /// Only check that the file is a loadable game, without replacing the current one
+        #[arg(long)]
+        verify: bool,
+    },
+    /// List the named save slots
+    #[command(after_help = "Example:\n  hangman slots")]
+    Slots,
+    /// List the word categories available to `new --category`
+    #[command(after_help = "Example:\n  hangman categories")]
+    Categories,
+    /// Abandon the current game without starting a new one
+    #[command(after_help = "Example:\n  hangman reset --yes")]
+    Reset {
+        /// Reset without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
     },
     /// Show the current word with the guessed characters
-    Show,
+    #[command(
+        after_help = "Examples:\n  hangman show --progress --frequencies\n  hangman show --compact"
+    )]
+    Show(ShowArgs),
+    /// Play interactively, guessing one letter at a time until the game is over
+    #[command(after_help = "Examples:\n  hangman play\n  hangman play --autosave false")]
+    Play {
+        /// Save the game after every guess instead of only when the game ends or is interrupted
+        #[arg(long, value_name = "BOOL", num_args = 0..=1, default_value_t = true, default_missing_value = "true", action = clap::ArgAction::Set)]
+        autosave: bool,
+    },
+    /// Show the statistics of all finished games
+    #[command(after_help = "Example:\n  hangman stats")]
+    Stats,
+    /// Show when each guess of the current game was made
+    #[command(after_help = "Examples:\n  hangman history\n  hangman history --since 2024-01-01")]
+    History {
+        /// Only show the guesses made from this date or time on, like 2024-01-01 or 2024-01-01 18:30:00
+        #[arg(long, value_name = "DATE", value_parser = parse_since)]
+        since: Option<DateTime<Local>>,
+    },
+    /// Show the best won games
+    #[command(after_help = "Example:\n  hangman leaderboard -n 5")]
+    Leaderboard {
+        /// How many games to show
+        #[arg(short = 'n', long, default_value = "10")]
+        top: usize,
+    },
+    /// Revert the last guess of the current game
+    #[command(after_help = "Example:\n  hangman undo")]
+    Undo,
+    /// Replay the guesses of the current game step by step
+    #[command(after_help = "Example:\n  hangman replay --speed 300")]
+    Replay {
+        /// The milliseconds to wait between guesses
+        #[arg(short, long, default_value = "800")]
+        speed: u64,
+    },
+    /// Reveal a random letter of the word, at the cost of one strike
+    ///
+    /// The first `free_hints` hints of a game, as configured, cost no strike
+    #[command(after_help = "Examples:\n  hangman hint --free\n  hangman hint --count")]
+    Hint {
+        /// Reveal the letter without spending a strike
+        #[arg(long)]
+        free: bool,
+        /// Only tell how many different letters are left to find, revealing none and costing nothing
+        #[arg(long, conflicts_with = "free")]
+        count: bool,
+    },
     /// Generate completion scripts for various shells
+    #[command(
+        after_help = "Examples:\n  hangman completions --shell bash --directory completions\n  source <(hangman completions --shell bash --stdout)"
+    )]
     Completions {
         #[arg(short, long, value_name = "DIRECTORY")]
         directory: Option<PathBuf>,
+        /// Only generate the completion script for this shell
+        #[arg(short, long)]
+        shell: Option<Shell>,
+        /// Print the completion script to stdout instead of writing a file, like for `source <(...)`
+        #[arg(long, requires = "shell", conflicts_with = "directory")]
+        stdout: bool,
+    },
+    /// Manage the words fetched ahead of time from the API
+    #[command(after_help = "Example:\n  hangman cache refill --count 50")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
     },
+    /// Inspect the configuration
+    #[command(
+        after_help = "Examples:\n  hangman config show\n  hangman config init --path hangman.toml"
+    )]
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Analyze a wordlist, to spot problems before playing with it
+    #[command(
+        after_help = "Examples:\n  hangman wordlist-stats --file words.txt\n  hangman --json wordlist-stats --file words.txt.gz"
+    )]
+    WordlistStats {
+        /// The wordlist to analyze
+        #[arg(short, long, value_name = "FILE")]
+        file: PathBuf,
+    },
+}
+
+impl Commands {
+    /// Checks if the command reads the current game, so the savefile has to exist
+    fn reads_savefile(&self) -> bool {
+        matches!(
+            self,
+            Commands::Guess { .. }
+                | Commands::Query { .. }
+                | Commands::Save { .. }
+                | Commands::Show(_)
+                | Commands::Play { .. }
+                | Commands::History { .. }
+                | Commands::Undo
+                | Commands::Replay { .. }
+                | Commands::Hint { .. }
+        )
+    }
+
+    /// Checks if a missing savefile is created for the command, only commands that
+    /// change the current game do so, the others fail as no game is active
+    fn creates_savefile(&self) -> bool {
+        self.reads_savefile() && self.mutates_savefile()
+    }
+
+    /// Checks if the command replaces the current game without reading it
+    fn writes_savefile(&self) -> bool {
+        matches!(
+            self,
+            Commands::New(_) | Commands::Load { verify: false, .. } | Commands::Reset { .. }
+        )
+    }
+
+    /// Checks if the command changes the current game, so it is backed up with `--backup`
+    fn mutates_savefile(&self) -> bool {
+        match self {
+            Commands::New(args) => !args.dry_run,
+            Commands::Hint { count, .. } => !count,
+            Commands::Guess { .. } | Commands::Undo | Commands::Play { .. } => true,
+            command => command.writes_savefile(),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum CacheCommands {
+    /// Fetch a batch of words from the API into the word cache
+    Refill {
+        /// How many words to fetch
+        #[arg(short, long, default_value = "50")]
+        count: usize,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ConfigCommands {
+    /// Print the resolved configuration, with where each value came from
+    Show,
+    /// Print the path of the configuration file in use
+    Path,
+    /// Write a configuration file with every default value and examples of the unset options, commented out
+    Init {
+        /// Where to write the configuration file, defaults to hangman.toml in the configuration directory
+        #[arg(short, long, value_name = "FILE")]
+        path: Option<PathBuf>,
+        /// Overwrite the file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// The difficulty given on the command line, see [`Difficulty`]
+#[derive(ValueEnum, Debug, Clone, Copy)]
+enum DifficultyArg {
+    /// Words of 3 to 5 letters
+    Easy,
+    /// Words of 6 to 8 letters
+    Medium,
+    /// Words of 9 or more letters
+    Hard,
+}
+
+impl From<DifficultyArg> for Difficulty {
+    fn from(difficulty: DifficultyArg) -> Self {
+        match difficulty {
+            DifficultyArg::Easy => Difficulty::Easy,
+            DifficultyArg::Medium => Difficulty::Medium,
+            DifficultyArg::Hard => Difficulty::Hard,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone, Default)]
+struct NewArgs {
+    /// The wordlist to pick the word from, use - to read a single word from stdin
+    #[arg(short, long, value_name = "OPTIONAL FILE")]
+    file: Option<PathBuf>,
+    /// The word to guess, for letting another player guess it
+    ///
+    /// Use - to type the word without showing it on the terminal
+    #[arg(short, long, conflicts_with = "file")]
+    word: Option<String>,
+    /// The category to pick the word from, see `categories`
+    #[arg(long, conflicts_with_all = ["file", "word"])]
+    category: Option<String>,
+    /// The difficulty of the word, overrides the configured difficulty
+    #[arg(long)]
+    difficulty: Option<DifficultyArg>,
+    /// Pick the difficulty from the recent games, harder after wins and easier after losses
+    #[arg(long, conflicts_with = "difficulty")]
+    adaptive: bool,
+    /// The most letters the word may have, overrides the configured maximum
+    #[arg(long)]
+    max_length: Option<usize>,
+    /// The amount of strikes for this game, overrides the configured strikes
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(1..=MAX_STRIKES as i64))]
+    strikes: Option<u8>,
+    /// Pick the word and report where it came from, without starting the game
+    #[arg(long)]
+    dry_run: bool,
+    /// Seed the word selection, so the same seed and wordlist always give the same word
+    ///
+    /// Without a file or category the offline wordlist is used, as the API can't be seeded
+    #[arg(long)]
+    seed: Option<u64>,
+    /// The name of the player, shown on the leaderboard if the game is won
+    #[arg(long)]
+    name: Option<String>,
+    /// The names of players taking turns in `play`, like "alice,bob"
+    #[arg(long, value_delimiter = ',')]
+    players: Vec<String>,
+    /// The language of the word, one of en, es, fr or de, overrides the configured language
+    #[arg(long)]
+    lang: Option<String>,
+    /// Replace a game in progress without asking for confirmation
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args, Debug, Clone, Default)]
+struct ShowArgs {
+    /// Don't show which letters of the alphabet have been guessed
+    #[arg(long)]
+    no_alphabet: bool,
+    /// Also print the unmasked word, after asking for confirmation
+    #[arg(long)]
+    reveal: bool,
+    /// Reveal the word without asking for confirmation
+    #[arg(long, requires = "reveal")]
+    i_give_up: bool,
+    /// Also print how many times each correctly guessed letter appears in the word
+    #[arg(long)]
+    frequencies: bool,
+    /// The character shown for unguessed letters, overrides the configured mask character
+    #[arg(long)]
+    mask_char: Option<char>,
+    /// Show the word and the alphabet in uppercase, overrides the configured display case
+    #[arg(long)]
+    upper: bool,
+    /// Also print how many of the letters of the word have been found
+    #[arg(long)]
+    progress: bool,
+    /// Print the game on a single uncolored line, for status bars and prompts
+    #[arg(long, conflicts_with_all = ["reveal", "frequencies", "progress"])]
+    compact: bool,
+}
+
+/// Guesses the characters, skipping the ones already guessed unless `strict`
+/// is set, in which case each of them costs a strike
+fn handle_guess(
+    guess: String,
+    strict: bool,
+    config: &Config,
+    store: &mut dyn SaveStore,
+    statsfile_path: PathBuf,
+    leaderboard_path: PathBuf,
+) -> anyhow::Result<GameStatus> {
+    let matching = config.matching();
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    let strikes_before = savefile.strikes_left;
+    report_guess(&mut savefile, &guess, strict, matching);
+
+    conclude_guess(
+        &savefile,
+        strikes_before,
+        config,
+        store,
+        &statsfile_path,
+        &leaderboard_path,
+    )?;
+    Ok(savefile.status)
+}
+
+/// Applies a list of guessed characters to the game until it is over, printing
+/// the characters that were skipped or cost a strike and the outcome
+fn report_guess(savefile: &mut Savefile, guess: &str, strict: bool, matching: Matching) {
+    let outcome = guess_characters(savefile, guess, strict, matching);
+    if informational_output() {
+        if !outcome.skipped.is_empty() {
+            println!(
+                "Skipping characters that were already guessed: {}",
+                outcome
+                    .skipped
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+        }
+        for character in &outcome.penalized {
+            println!("'{}' was already guessed, costing a strike", character);
+        }
+    }
+    announce_outcome(savefile, matching);
+}
+
+/// Applies the guesses of a file line by line, stopping once the game is over
+fn handle_guess_file(
+    file: PathBuf,
+    strict: bool,
+    config: &Config,
+    store: &mut dyn SaveStore,
+    statsfile_path: PathBuf,
+    leaderboard_path: PathBuf,
+) -> anyhow::Result<GameStatus> {
+    let matching = config.matching();
+    let guesses = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read the guesses file {:?}", file))?;
+    let guesses = guesses
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>();
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    let strikes_before = savefile.strikes_left;
+    for (applied, guess) in guesses.iter().enumerate() {
+        debug!("Applying guess {} from {:?}: {}", applied + 1, file, guess);
+        report_guess(&mut savefile, guess, strict, matching);
+        if savefile.status != GameStatus::InProgress {
+            if applied + 1 < guesses.len() && informational_output() {
+                println!(
+                    "The game ended after {} of the {} guesses in {:?}, the rest were not applied",
+                    applied + 1,
+                    guesses.len(),
+                    file
+                );
+            }
+            break;
+        }
+    }
+
+    conclude_guess(
+        &savefile,
+        strikes_before,
+        config,
+        store,
+        &statsfile_path,
+        &leaderboard_path,
+    )?;
+    if !JSON_OUTPUT.load(Ordering::Relaxed) {
+        println!(
+            "{}",
+            render_game(&savefile, matching, config.mask_style(), true, &Terminal)
+        );
+    }
+    Ok(savefile.status)
+}
+
+fn handle_guess_word(
+    word: String,
+    config: &Config,
+    store: &mut dyn SaveStore,
+    statsfile_path: PathBuf,
+    leaderboard_path: PathBuf,
+) -> anyhow::Result<GameStatus> {
+    let matching = config.matching();
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    let strikes_before = savefile.strikes_left;
+    let right = guess_word(&mut savefile, &word, config.word_guess_penalty, matching);
+    if !right && informational_output() {
+        let cost = strikes_before - savefile.strikes_left;
+        let strikes = if cost == 1 { "strike" } else { "strikes" };
+        println!("'{}' is not the word, costing {} {}", word, cost, strikes);
+    }
+    announce_outcome(&savefile, matching);
+
+    conclude_guess(
+        &savefile,
+        strikes_before,
+        config,
+        store,
+        &statsfile_path,
+        &leaderboard_path,
+    )?;
+    Ok(savefile.status)
+}
+
+/// Warns when few strikes are left, then saves the game and prints it as JSON if requested
+fn conclude_guess(
+    savefile: &Savefile,
+    strikes_before: u8,
+    config: &Config,
+    store: &mut dyn SaveStore,
+    statsfile_path: &Path,
+    leaderboard_path: &Path,
+) -> anyhow::Result<()> {
+    if savefile.status == GameStatus::InProgress
+        && savefile.strikes_left < strikes_before
+        && savefile.strikes_left <= config.warn_at_strikes
+        && !JSON_OUTPUT.load(Ordering::Relaxed)
+    {
+        let strikes = if savefile.strikes_left == 1 {
+            "strike"
+        } else {
+            "strikes"
+        };
+        println!("⚠ {} {} left!", savefile.strikes_left, strikes);
+    }
+
+    finish_turn(
+        savefile,
+        store,
+        statsfile_path,
+        leaderboard_path,
+        config.scoring(),
+    )?;
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        print_json(&GameJson::new(savefile, config.matching(), false))?;
+    }
+    Ok(())
+}
+
+fn handle_eliminate(
+    guess: String,
+    matching: Matching,
+    store: &mut dyn SaveStore,
+) -> anyhow::Result<()> {
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    for character in normalize_guess(&guess, matching) {
+        if savefile.guessed.contains(&character) {
+            info!(
+                "Character '{}' has already been guessed, not eliminating it",
+                character
+            );
+        } else if !savefile.eliminated.contains(&character) {
+            info!("Eliminating character '{}'", character);
+            savefile.eliminated.push(character);
+        }
+    }
+    store.save(&savefile)?;
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        print_json(&GameJson::new(&savefile, matching, false))?;
+    }
+    Ok(())
+}
+
+/// Checks if the game has been won or lost, printing the outcome
+///
+/// Returns true if the game is over
+fn announce_status(savefile: &mut Savefile, matching: Matching) -> bool {
+    let over = update_status(savefile, matching);
+    announce_outcome(savefile, matching);
+    over
+}
+
+/// Prints if the game was won or lost, nothing while it is in progress
+fn announce_outcome(savefile: &Savefile, matching: Matching) {
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        return;
+    }
+    match savefile.status {
+        GameStatus::Won => println!("You won! The word was {}", savefile.word),
+        GameStatus::Lost => {
+            animate_loss(savefile, matching);
+            println!("You lost! The word was {}", savefile.word);
+        }
+        GameStatus::InProgress => {}
+    }
+}
+
+/// Fills in the letters that weren't found one at a time, when enabled with `guess --animate`
+fn animate_loss(savefile: &Savefile, matching: Matching) {
+    let Some((delay, style)) = *LOSS_ANIMATION.lock().expect("Animation lock was poisoned") else {
+        return;
+    };
+    if !informational_output() {
+        return;
+    }
+    let mut reveal = savefile.clone();
+    print!("{}", render_mask(&reveal, matching, style));
+    for c in savefile.word.chars() {
+        if reveal.is_revealed(c, matching) {
+            continue;
+        }
+        std::thread::sleep(delay);
+        reveal.correct.push(c);
+        print!("\r{}", render_mask(&reveal, matching, style));
+        let _ = std::io::stdout().flush();
+    }
+    println!();
+}
+
+/// Writes the savefile, and records the game in the stats and leaderboard if it is over
+fn finish_turn(
+    savefile: &Savefile,
+    store: &mut dyn SaveStore,
+    statsfile_path: &Path,
+    leaderboard_path: &Path,
+    scoring: Scoring,
+) -> anyhow::Result<()> {
+    store.save(savefile)?;
+    if savefile.status != GameStatus::InProgress {
+        let score = scoring.score(savefile);
+        if informational_output() {
+            println!("{}", render_summary(savefile));
+        }
+        if savefile.status == GameStatus::Won && informational_output() {
+            println!("Score: {}", score);
+        }
+        let mut stats = load_stats(statsfile_path)?;
+        stats.record(savefile, score);
+        write_stats(statsfile_path, &stats)?;
+        debug!("Recorded finished game in {:?}", statsfile_path);
+        if savefile.status == GameStatus::Won {
+            let mut leaderboard = load_leaderboard(leaderboard_path)?;
+            leaderboard.record(savefile, score);
+            write_leaderboard(leaderboard_path, &leaderboard)?;
+            debug!("Recorded won game in {:?}", leaderboard_path);
+        }
+    }
+    Ok(())
+}
+
+fn handle_hint(
+    free: bool,
+    config: &Config,
+    store: &mut dyn SaveStore,
+    statsfile_path: PathBuf,
+    leaderboard_path: PathBuf,
+) -> anyhow::Result<()> {
+    let matching = config.matching();
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    let hidden = savefile
+        .word
+        .chars()
+        .filter(|c| !savefile.is_revealed(*c, matching))
+        .collect::<Vec<char>>();
+    let Some(letter) = hidden.choose(&mut thread_rng()) else {
+        if informational_output() {
+            println!("There are no letters left to reveal");
+        }
+        return Ok(());
+    };
+    let letter = normalize_guess(&letter.to_string(), matching)[0];
+    debug!("Revealing letter '{}'", letter);
+
+    let budgeted = savefile.hints_used < config.free_hints;
+    savefile.guessed.push(letter);
+    savefile.correct.push(letter);
+    savefile.hints_used += 1;
+    if budgeted && informational_output() {
+        println!(
+            "Used a free hint, {} left",
+            config.free_hints - savefile.hints_used
+        );
+    }
+    if !free && !budgeted {
+        savefile.strikes_left = savefile.strikes_left.saturating_sub(1);
+    }
+    if informational_output() {
+        println!("{}", render_mask(&savefile, matching, config.mask_style()));
+    }
+    announce_status(&mut savefile, matching);
+
+    finish_turn(
+        &savefile,
+        store,
+        &statsfile_path,
+        &leaderboard_path,
+        config.scoring(),
+    )?;
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        print_json(&GameJson::new(&savefile, matching, false))?;
+    }
+    Ok(())
+}
+
+/// Tells how many different letters of the word are left to find, without changing the game
+fn handle_hint_count(matching: Matching, store: &dyn SaveStore) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+    savefile.ensure_active()?;
+
+    let left = savefile
+        .word
+        .chars()
+        .filter(|c| !savefile.is_revealed(*c, matching))
+        .map(|c| matching.fold(&c.to_string()))
+        .collect::<BTreeSet<String>>()
+        .len();
+    match left {
+        1 => println!("1 different letter is left to find"),
+        _ => println!("{} different letters are left to find", left),
+    }
+    Ok(())
+}
+
+fn handle_query(
+    check: Option<String>,
+    count: bool,
+    matching: Matching,
+    store: &dyn SaveStore,
+) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+    savefile.ensure_active()?;
+
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        return match check {
+            Some(check) => print_json(
+                &normalize_guess(&check, matching)
+                    .into_iter()
+                    .map(|character| CheckJson {
+                        character,
+                        guessed: savefile.guessed.contains(&character),
+                        correct: savefile.correct.contains(&character),
+                    })
+                    .collect::<Vec<CheckJson>>(),
+            ),
+            None => print_json(&GameJson::new(&savefile, matching, false)),
+        };
+    }
+
+    match check {
+        Some(check) => {
+            for character in normalize_guess(&check, matching) {
+                if !savefile.guessed.contains(&character) {
+                    println!("'{}' has not been guessed", character);
+                } else if savefile.correct.contains(&character) {
+                    println!(
+                        "'{}' has been guessed and is correct",
+                        mark_guess(&character.to_string(), true)
+                    );
+                } else {
+                    println!(
+                        "'{}' has been guessed and is incorrect",
+                        mark_guess(&character.to_string(), false)
+                    );
+                }
+            }
+        }
+        None => {
+            for character in &savefile.guessed {
+                let correct = savefile.correct.contains(character);
+                let status = if correct { '✓' } else { '✗' };
+                println!(
+                    "{}",
+                    color_guess(&format!("{} {}", character, status), correct)
+                );
+            }
+            if count {
+                println!("Total guesses: {}", savefile.guessed.len());
+                println!("Correct guesses: {}", savefile.correct.len());
+                println!("Incorrect guesses: {}", savefile.incorrect.len());
+                println!("Strikes left: {}", savefile.strikes_left);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses the date or time given to `history --since`, in the local timezone unless it has an offset
+fn parse_since(since: &str) -> Result<DateTime<Local>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(since) {
+        return Ok(time.with_timezone(&Local));
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(since, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            format!(
+                "{:?} is not a date like 2024-01-01 or a time like 2024-01-01 18:30:00",
+                since
+            )
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("{:?} does not exist in the local timezone", since))
+}
+
+fn handle_history(since: Option<DateTime<Local>>, store: &dyn SaveStore) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+    savefile.ensure_active()?;
+
+    let history = savefile
+        .history
+        .iter()
+        .filter(|(time, _, _)| since.is_none_or(|since| *time >= since))
+        .collect::<Vec<_>>();
+    if savefile.history.is_empty() {
+        println!("No guesses have been made yet");
+    } else if let Some(since) = since.filter(|_| history.is_empty()) {
+        println!(
+            "No guesses have been made since {}",
+            since.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    for (time, character, correct) in history {
+        let status = if *correct { '✓' } else { '✗' };
+        println!(
+            "{} {}",
+            time.format("%Y-%m-%d %H:%M:%S"),
+            color_guess(&format!("{} {}", character, status), *correct)
+        );
+    }
+    Ok(())
+}
+
+fn handle_undo(store: &mut dyn SaveStore) -> anyhow::Result<()> {
+    let mut savefile = store.load()?;
+    savefile.ensure_in_progress()?;
+
+    let Some((character, correct)) = savefile.undo_guess() else {
+        bail!("There are no guesses to undo");
+    };
+    store.save(&savefile)?;
+    println!(
+        "Undid the guess '{}'",
+        mark_guess(&character.to_string(), correct)
+    );
+    Ok(())
+}
+
+/// Reads a wordlist file, decompressing it first if it has a `.gz` extension
+fn read_wordlist(path: &Path) -> anyhow::Result<String> {
+    let gzipped = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("gz"));
+    if !gzipped {
+        return std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read wordlist {:?}", path));
+    }
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to read wordlist {:?}", path))?;
+    let mut wordlist = String::new();
+    GzDecoder::new(file)
+        .read_to_string(&mut wordlist)
+        .with_context(|| format!("Failed to decompress wordlist {:?}", path))?;
+    Ok(wordlist)
+}
+
+/// Warns that the picked word is predictable when a wordlist of the user has few usable words
+///
+/// An empty list is left to the caller, as no word can be picked at all
+fn warn_few_words(words: &[String], name: &str, min_words: usize) {
+    if !words.is_empty() && words.len() < min_words {
+        warn!(
+            "{} only has {} usable words matching the difficulty and length, the picked word is predictable",
+            name,
+            words.len()
+        );
+    }
+}
+
+fn handle_categories(categories_dir: Option<PathBuf>) -> anyhow::Result<()> {
+    for category in available_categories(categories_dir.as_deref())? {
+        println!("{}", category);
+    }
+    Ok(())
+}
+
+/// How many times the API is asked for a word before giving up
+const API_ATTEMPTS: u32 = 20;
+
+/// How many malformed API responses are tolerated before giving up on the API
+const API_MALFORMED_ATTEMPTS: u32 = 2;
+
+/// Gets the URL of the word API, asking for words of the language unless it is English
+fn api_url(config: &Config, language: &str) -> anyhow::Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&config.word_api_url)
+        .with_context(|| format!("Invalid word API URL {}", config.word_api_url))?;
+    if language != DEFAULT_LANGUAGE {
+        let pairs = url
+            .query_pairs()
+            .filter(|(key, _)| key != "lang")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect::<Vec<(String, String)>>();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("lang", language);
+    }
+    Ok(url)
+}
+
+/// Normalizes a word given by the API, rejecting anything that isn't purely letters
+fn clean_api_word(word: String) -> Option<String> {
+    let word = word.trim().nfc().collect::<String>();
+    (!word.is_empty() && word.chars().all(|c| c.is_alphabetic())).then_some(word)
+}
+
+fn api_client(config: &Config) -> reqwest::Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(config.word_api_timeout_secs))
+        .build()
+}
+
+/// Gets a random word accepted by the filter from the API
+///
+/// Returns `None` if the API can't be reached or doesn't give an accepted
+/// word in time
+fn fetch_api_word(
+    config: &Config,
+    language: &str,
+    accepts: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let url = match api_url(config, language) {
+        Ok(url) => url,
+        Err(err) => {
+            debug!("For debug purposes, the API URL error is: {:?}", err);
+            return None;
+        }
+    };
+    let client = match api_client(config) {
+        Ok(client) => client,
+        Err(err) => {
+            debug!("For debug purposes, the HTTP client error is: {:?}", err);
+            return None;
+        }
+    };
+    let mut malformed = 0;
+    for _ in 0..API_ATTEMPTS {
+        let api_response = match client.get(url.clone()).send() {
+            Ok(response) => response,
+            Err(err) => {
+                debug!("For debug purposes, the API error is: {:?}", err);
+                return None;
+            }
+        };
+        let text = match api_response.text() {
+            Ok(text) => text,
+            Err(err) => {
+                debug!("For debug purposes, the API error is: {:?}", err);
+                return None;
+            }
+        };
+        debug!("Raw API response: {:?}", text);
+        let word = config.word_api_format.parse(&text).and_then(clean_api_word);
+        let Some(word) = word else {
+            malformed += 1;
+            if malformed >= API_MALFORMED_ATTEMPTS {
+                debug!("The API gave malformed responses, giving up");
+                return None;
+            }
+            debug!("The API response is not a usable word, retrying");
+            continue;
+        };
+        if accepts(&word) {
+            return Some(word);
+        }
+        debug!("API word does not match the difficulty or length, retrying");
+    }
+    None
+}
+
+/// Gets the words from the configured word API
+struct ApiWordSource<'a> {
+    config: &'a Config,
+}
+
+impl WordSource for ApiWordSource<'_> {
+    fn fetch_word(&self, language: &str, accepts: &dyn Fn(&str) -> bool) -> Option<String> {
+        fetch_api_word(self.config, language, accepts)
+    }
+
+    fn describe(&self) -> String {
+        format!("the word API at {}", self.config.word_api_url)
+    }
+}
+
+/// Gets the wordlist hosted at the URL, downloading it only if it isn't cached yet
+///
+/// The cache starts with a `#` comment naming the URL, so a changed URL is
+/// downloaded again. Returns `None` if the wordlist can't be downloaded, or
+/// isn't cached and `offline` is set
+fn url_wordlist(url: &str, config: &Config, offline: bool) -> anyhow::Result<Option<String>> {
+    let header = format!("# Downloaded from {}", url);
+    if let Some(cache) = &config.wordlist_url_cache {
+        if let Ok(wordlist) = std::fs::read_to_string(cache) {
+            if wordlist.lines().next() == Some(header.as_str()) {
+                debug!("Using the cached wordlist {:?}", cache);
+                return Ok(Some(wordlist));
+            }
+        }
+    }
+    if config.offline_only || offline {
+        warn!(
+            "Not using the network, not downloading the wordlist at {}",
+            url
+        );
+        return Ok(None);
+    }
+
+    let wordlist = match api_client(config)
+        .and_then(|client| client.get(url).send())
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+    {
+        Ok(wordlist) => wordlist,
+        Err(err) => {
+            warn!("Failed to download the wordlist at {}: {}", url, err);
+            return Ok(None);
+        }
+    };
+    let wordlist = format!("{}\n{}", header, wordlist);
+    if let Some(cache) = &config.wordlist_url_cache {
+        if let Some(parent) = cache.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory {:?}", parent))?;
+        }
+        write_atomic(cache, wordlist.as_bytes())?;
+        info!("Cached the wordlist at {} in {:?}", url, cache);
+    }
+    Ok(Some(wordlist))
+}
+
+fn handle_cache(
+    action: CacheCommands,
+    config: &Config,
+    cache_path: PathBuf,
+    no_network: bool,
+) -> anyhow::Result<()> {
+    match action {
+        CacheCommands::Refill { count } => {
+            if config.offline_only {
+                bail!("Offline only is set, not fetching words from the API");
+            }
+            if no_network {
+                bail!("--no-network was given, not fetching words from the API");
+            }
+            let mut url = reqwest::Url::parse(&config.word_api_url)
+                .with_context(|| format!("Invalid word API URL {}", config.word_api_url))?;
+            let pairs = url
+                .query_pairs()
+                .filter(|(key, _)| key != "words")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect::<Vec<(String, String)>>();
+            url.query_pairs_mut()
+                .clear()
+                .extend_pairs(pairs)
+                .append_pair("words", &count.to_string());
+
+            let text = api_client(config)
+                .and_then(|client| client.get(url.clone()).send())
+                .and_then(|response| response.text())
+                .with_context(|| format!("Failed to fetch words from {}", url))?;
+            debug!("Raw API response: {:?}", text);
+            let words = config
+                .word_api_format
+                .parse_all(&text)
+                .into_iter()
+                .filter_map(clean_api_word)
+                .collect::<Vec<String>>();
+            if words.is_empty() {
+                bail!("The word API gave no usable words");
+            }
+
+            if let Some(parent) = cache_path.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create word cache directory")?;
+            }
+            let mut contents = if cache_path.exists() {
+                std::fs::read_to_string(&cache_path)
+                    .with_context(|| format!("Failed to read word cache {:?}", cache_path))?
+            } else {
+                String::new()
+            };
+            for word in &words {
+                contents.push_str(word);
+                contents.push('\n');
+            }
+            write_atomic(&cache_path, contents.as_bytes())?;
+            println!("Added {} words to the word cache", words.len());
+        }
+    }
+    Ok(())
+}
+
+//noinspection SpellCheckingInspection
+/// Checks if the store holds a game that is neither won nor lost
+fn game_in_progress(store: &dyn SaveStore) -> bool {
+    store
+        .load()
+        .is_ok_and(|savefile| savefile.is_active() && savefile.status == GameStatus::InProgress)
+}
+
+fn handle_new(
+    args: NewArgs,
+    config: &Config,
+    words: &dyn WordSource,
+    store: &mut dyn SaveStore,
+    statsfile_path: &Path,
+) -> anyhow::Result<()> {
+    if !args.force && !args.dry_run && game_in_progress(store) {
+        if JSON_OUTPUT.load(Ordering::Relaxed) || !std::io::stdin().is_terminal() {
+            bail!("A game is in progress, pass --force to replace it");
+        }
+        if !confirm("A game is in progress, replace it?")? {
+            info!("Not replacing the game in progress, exiting");
+            return Ok(());
+        }
+    }
+    //noinspection SpellCheckingInspection
+    let random_word: String;
+    let source: String;
+    let difficulty = if args.adaptive {
+        let difficulty = Difficulty::adaptive(&load_stats(statsfile_path)?.recent);
+        info!("Adapted the difficulty to {:?}", difficulty);
+        Some(difficulty)
+    } else {
+        args.difficulty.map(Difficulty::from).or(config.difficulty)
+    };
+    let strikes = args.strikes.unwrap_or(config.strikes);
+    if config.word_guess_penalty > strikes {
+        bail!(
+            "The configured word_guess_penalty of {} is more than the {} strikes of the game",
+            config.word_guess_penalty,
+            strikes
+        );
+    }
+    let max_length = args.max_length.or(config.max_word_length);
+    let language = bundled_language(args.lang.as_deref().unwrap_or(&config.language));
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let accepts = |word: &str| {
+        difficulty.is_none_or(|d| d.accepts(word))
+            && max_length.is_none_or(|max| letter_count(word) <= max)
+    };
+
+    if let Some(word) = args.word {
+        let word = if word == "-" {
+            rpassword::prompt_password("Enter the secret word: ")
+                .context("Failed to read the secret word")?
+        } else {
+            word
+        };
+        info!("Starting new game with a given word");
+        random_word = validate_word(&word)?;
+        source = "the given word".to_string();
+    } else if args.file.as_deref() == Some(Path::new("-")) {
+        info!("Starting new game with a word from stdin");
+        let mut word = String::new();
+        std::io::stdin()
+            .read_line(&mut word)
+            .context("Failed to read the word from stdin")?;
+        random_word = validate_word(&word)?;
+        source = "stdin".to_string();
+    } else if let Some(file_path) = args.file {
+        info!("Starting new game with wordfile: {:?}", file_path);
+        if !file_path.exists() {
+            bail!("Wordlist file {:?} does not exist", file_path);
+        } else if file_path.is_dir() {
+            bail!("Given wordlist {:?} is a directory", file_path);
+        }
+        let wordlist = read_wordlist(&file_path)?;
+        let words = usable_words(&wordlist, accepts);
+        warn_few_words(
+            &words,
+            &format!("Wordlist {:?}", file_path),
+            config.min_wordlist_words,
+        );
+        random_word = words.choose(&mut rng).cloned().with_context(|| {
+            format!(
+                "Wordlist {:?} has no usable words matching the difficulty and length",
+                file_path
+            )
+        })?;
+        debug!(
+            "Successfully generated random word from file: {}",
+            random_word
+        );
+        source = format!("wordlist {:?}", file_path);
+    } else if let Some(category) = args.category {
+        info!("Starting new game with category: {}", category);
+        let wordlist = category_wordlist(&category, config.categories.as_deref())?;
+        random_word = pick_word(&wordlist, accepts, &mut rng).with_context(|| {
+            format!(
+                "Category {} has no usable words matching the difficulty and length",
+                category
+            )
+        })?;
+        debug!(
+            "Successfully generated random word from category: {}",
+            random_word
+        );
+        source = format!("category {}", category);
+    } else if let Some(wordlists) = &config.wordlist {
+        info!("Starting new game with the configured wordlists");
+        let mut combined = String::new();
+        for path in wordlists.paths() {
+            match read_wordlist(path) {
+                Ok(wordlist) => {
+                    combined.push_str(&wordlist);
+                    combined.push('\n');
+                }
+                Err(err) => warn!("Skipping wordlist {:?}: {:#}", path, err),
+            }
+        }
+        let words = usable_words(&combined, accepts);
+        warn_few_words(
+            &words,
+            "The configured wordlists",
+            config.min_wordlist_words,
+        );
+        random_word = words.choose(&mut rng).cloned().context(
+            "The configured wordlists have no usable words matching the difficulty and length",
+        )?;
+        debug!(
+            "Successfully generated random word from the configured wordlists: {}",
+            random_word
+        );
+        source = "the configured wordlists".to_string();
+    } else if let Some((url, wordlist)) = match &config.wordlist_url {
+        Some(url) => url_wordlist(url, config, words.offline())?.map(|wordlist| (url, wordlist)),
+        None => None,
+    } {
+        info!("Starting new game with the wordlist at {}", url);
+        let words = usable_words(&wordlist, accepts);
+        warn_few_words(
+            &words,
+            &format!("The wordlist at {}", url),
+            config.min_wordlist_words,
+        );
+        random_word = words.choose(&mut rng).cloned().with_context(|| {
+            format!(
+                "The wordlist at {} has no usable words matching the difficulty and length",
+                url
+            )
+        })?;
+        debug!(
+            "Successfully generated random word from the wordlist URL: {}",
+            random_word
+        );
+        source = format!("the wordlist at {}", url);
+    } else if let Some(word) = match (&config.word_cache, args.seed) {
+        (Some(cache), None) if language == DEFAULT_LANGUAGE => {
+            take_cached_word(cache, accepts, !args.dry_run)?
+        }
+        _ => None,
+    } {
+        debug!(
+            "Successfully took random word from the word cache: {}",
+            word
+        );
+        random_word = word;
+        source = "the word cache".to_string();
+    } else {
+        let api_word = if config.offline_only {
+            info!("Offline only is set, skipping the API");
+            None
+        } else if args.seed.is_some() {
+            info!("A seed was given, skipping the API");
+            None
+        } else {
+            words.fetch_word(language, &accepts)
+        };
+        (random_word, source) = match api_word {
+            Some(word) => {
+                debug!("Successfully generated random word from API: {}", word);
+                (word, words.describe())
+            }
+            None => {
+                if args.seed.is_none() && !config.offline_only {
+                    warn!("Failed to get a word from the API, using the offline wordlist");
+                }
+                let word = pick_word(offline_wordlist(language), accepts, &mut rng).context(
+                    "The offline wordlist has no words matching the difficulty and length",
+                )?;
+                debug!(
+                    "Successfully generated random word from the offline wordlist: {}",
+                    word
+                );
+                (word, "the offline wordlist".to_string())
+            }
+        };
+    }
+
+    if args.dry_run {
+        println!(
+            "Picked a word of {} letters from {}, not starting the game",
+            letter_count(&random_word),
+            source
+        );
+        return Ok(());
+    }
+
+    store.save(&Savefile {
+        word: random_word,
+        guessed: vec![],
+        correct: vec![],
+        incorrect: vec![],
+        strikes_left: strikes,
+        strikes,
+        status: GameStatus::InProgress,
+        history: vec![],
+        hints_used: 0,
+        player: args.name,
+        eliminated: vec![],
+        started_at: Some(Local::now()),
+        finished_at: None,
+        players: args
+            .players
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| Player {
+                name: name.to_string(),
+                ..Player::default()
+            })
+            .collect(),
+        turn: 0,
+    })
+}
+
+/// Copies the savefile next to itself with a `.bak` extension added, replacing the last backup
+fn backup_savefile(savefile_path: &Path) -> anyhow::Result<()> {
+    let mut backup = savefile_path.as_os_str().to_owned();
+    backup.push(".bak");
+    let backup = PathBuf::from(backup);
+    std::fs::copy(savefile_path, &backup)
+        .with_context(|| format!("Failed to back up {:?} to {:?}", savefile_path, backup))?;
+    info!("Backed up the savefile to {:?}", backup);
+    Ok(())
+}
+
+/// Checks if the path is a TOML file, following symlinks and ignoring the case
+/// of the extension
+fn verify_toml_file(file: &Path) -> bool {
+    std::fs::canonicalize(file).is_ok_and(|target| target.is_file())
+        && file
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"))
+}
+
+/// Asks the user a yes/no question on stdin, defaulting to no
+fn confirm(prompt: &str) -> anyhow::Result<bool> {
+    print!("{} [y/N] ", prompt);
+    std::io::stdout()
+        .flush()
+        .context("Failed to flush stdout")?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read answer from stdin")?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+fn handle_save(
+    file: PathBuf,
+    force: bool,
+    store: &dyn SaveStore,
+    format: SaveFormat,
+) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+
+    if file.exists() && !force && !confirm(&format!("{:?} already exists, overwrite it?", file))? {
+        info!("Not overwriting {:?}, exiting", file);
+        return Ok(());
+    }
+    if let Some(parent) = file.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            info!("Directory {:?} does not exist, creating it", parent);
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create save directory {:?}", parent))?;
+        }
+    }
+
+    FileStore {
+        format: SaveFormat::from_path(&file).unwrap_or(format),
+        path: file.clone(),
+    }
+    .save(&savefile)?;
+    info!("Saved game to {:?}", file);
+    Ok(())
+}
+
+/// The directory holding the named save slots, next to the internal savefile
+fn slots_dir(savefile_path: &Path) -> PathBuf {
+    savefile_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("hangman_slots")
+}
+
+/// Resolves the file to save to or load from, either given directly or as a slot name
+fn resolve_save_target(
+    file: Option<PathBuf>,
+    slot: Option<String>,
+    savefile_path: &Path,
+    format: SaveFormat,
+) -> anyhow::Result<PathBuf> {
+    match (file, slot) {
+        (Some(file), _) => Ok(file),
+        (None, Some(slot)) => {
+            if slot.is_empty()
+                || !slot
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                bail!(
+                    "Slot name {:?} may only contain letters, numbers, '-' and '_'",
+                    slot
+                );
+            }
+            Ok(slots_dir(savefile_path).join(format!("{}.{}", slot, format.extension())))
+        }
+        (None, None) => bail!("Either a file or a slot must be given"),
+    }
+}
+
+fn handle_slots(savefile_path: PathBuf) -> anyhow::Result<()> {
+    let directory = slots_dir(&savefile_path);
+    let mut slots = vec![];
+    if directory.is_dir() {
+        for entry in std::fs::read_dir(&directory)
+            .with_context(|| format!("Failed to read slots directory {:?}", directory))?
+        {
+            let path = entry?.path();
+            if path.is_file() && SaveFormat::from_path(&path).is_some() {
+                if let Some(name) = path.file_stem() {
+                    slots.push(name.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+
+    if slots.is_empty() {
+        println!("There are no saved slots");
+    }
+    slots.sort();
+    for slot in slots {
+        println!("{}", slot);
+    }
+    Ok(())
+}
+
+/// Reads a savefile given by the user, in the format of its extension
+fn read_given_savefile(file: &Path) -> anyhow::Result<Savefile> {
+    let file_format = match SaveFormat::from_path(file) {
+        Some(file_format) if file.is_file() => file_format,
+        _ => bail!("Given file {:?} is not a valid TOML or JSON file", file),
+    };
+    load_savefile(file, file_format)
+}
+
+fn handle_load(file: PathBuf, store: &mut dyn SaveStore) -> anyhow::Result<()> {
+    let savefile = read_given_savefile(&file).with_context(|| {
+        format!(
+            "Given file {:?} is not a valid savefile, the current game was not changed",
+            file
+        )
+    })?;
+    debug!(
+        "Successfully read savefile with a word length of {}",
+        savefile.word.chars().count()
+    );
+
+    store.save(&savefile)?;
+    info!("Loaded game from {:?}", file);
+    Ok(())
+}
+
+/// Checks if the file is a loadable game, printing PASS or FAIL
+///
+/// Returns false if the file can't be loaded
+fn handle_verify(file: PathBuf) -> bool {
+    match read_given_savefile(&file) {
+        Ok(savefile) => {
+            println!(
+                "PASS: {:?} is a loadable game with a word of {} letters",
+                file,
+                letter_count(&savefile.word)
+            );
+            true
+        }
+        Err(e) => {
+            println!("FAIL: {:?} is not a loadable game: {:#}", file, e);
+            false
+        }
+    }
+}
+
+fn handle_reset(yes: bool, strikes: u8, store: &mut dyn SaveStore) -> anyhow::Result<()> {
+    if !yes && !confirm("Abandon the current game?")? {
+        info!("Not resetting the current game, exiting");
+        return Ok(());
+    }
+
+    store.save(&Savefile {
+        strikes_left: strikes,
+        strikes,
+        ..Savefile::default()
+    })?;
+    info!("Reset the current game");
+    Ok(())
+}
+
+/// Whether the game output is colored, see `--no-color`
+static COLOR_OUTPUT: AtomicBool = AtomicBool::new(true);
+
+/// Whether guesses are marked with ✓ and ✗, see the `use_symbols` config
+static SYMBOL_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether the game is printed as JSON instead of text, see `--json`
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether informational messages are left out, see `--quiet`
+static QUIET_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// The delay and mask style for filling in the word of a lost game, see `guess --animate`
+static LOSS_ANIMATION: Mutex<Option<(Duration, MaskStyle)>> = Mutex::new(None);
+
+/// Checks if messages beyond the essential game state should be printed
+fn informational_output() -> bool {
+    !QUIET_OUTPUT.load(Ordering::Relaxed) && !JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// The game as printed by `--json`
+#[derive(Debug, Serialize)]
+struct GameJson<'a> {
+    /// The word with every unguessed letter replaced by an underscore
+    masked: String,
+    /// The unmasked word, only set once the game is over or it was revealed
+    word: Option<&'a str>,
+    strikes_left: u8,
+    strikes: u8,
+    guessed: &'a [char],
+    /// How many guesses have been made
+    guesses: usize,
+    correct: &'a [char],
+    incorrect: &'a [char],
+    eliminated: &'a [char],
+    status: GameStatus,
+}
+
+impl<'a> GameJson<'a> {
+    fn new(savefile: &'a Savefile, matching: Matching, reveal: bool) -> Self {
+        let style = MaskStyle {
+            mask_char: '_',
+            case: DisplayCase::Original,
+        };
+        let masked = render_mask_with(savefile, matching, style, "", |letter| letter);
+        let over = savefile.status != GameStatus::InProgress;
+        GameJson {
+            masked,
+            word: (over || reveal).then_some(savefile.word.as_str()),
+            strikes_left: savefile.strikes_left,
+            strikes: savefile.strikes,
+            guessed: &savefile.guessed,
+            guesses: savefile.guessed.len(),
+            correct: &savefile.correct,
+            incorrect: &savefile.incorrect,
+            eliminated: &savefile.eliminated,
+            status: savefile.status,
+        }
+    }
+}
+
+/// A queried character as printed by `--json`
+#[derive(Debug, Serialize)]
+struct CheckJson {
+    character: char,
+    guessed: bool,
+    correct: bool,
+}
+
+fn print_json(value: &impl Serialize) -> anyhow::Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string(value).context("Failed to serialize the JSON output")?
+    );
+    Ok(())
+}
+
+/// Colors the text green for a correct guess or red for an incorrect one
+fn color_guess(text: &str, correct: bool) -> String {
+    if !COLOR_OUTPUT.load(Ordering::Relaxed) {
+        return text.to_string();
+    }
+    let code = if correct { 32 } else { 31 };
+    format!("\x1b[{}m{}\x1b[0m", code, text)
+}
+
+/// Colors a guess like `color_guess`, prefixed with ✓ or ✗ if symbols are used
+fn mark_guess(text: &str, correct: bool) -> String {
+    if !SYMBOL_OUTPUT.load(Ordering::Relaxed) {
+        return color_guess(text, correct);
+    }
+    let symbol = if correct { '✓' } else { '✗' };
+    color_guess(&format!("{}{}", symbol, text), correct)
+}
+
+/// Decorates the rendered guesses with `color_guess` and `mark_guess`
+struct Terminal;
+
+impl Paint for Terminal {
+    fn color(&self, text: &str, correct: bool) -> String {
+        color_guess(text, correct)
+    }
+
+    fn mark(&self, text: &str, correct: bool) -> String {
+        mark_guess(text, correct)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct Config {
-    wordlist: Option<PathBuf>,
-    savefile: Option<PathBuf>,
-    logfile: Option<PathBuf>,
-    strikes: u8,
+/// Renders the masked word, coloring the revealed letters
+fn render_mask(savefile: &Savefile, matching: Matching, style: MaskStyle) -> String {
+    render_mask_with(savefile, matching, style, " ", |letter| {
+        color_guess(&letter, true)
+    })
 }
 
-impl Default for Config {
-    //noinspection SpellCheckingInspection
-    fn default() -> Self {
-        let (savefile, logfile) = if cfg!(windows) {
-            (
-                PathBuf::from(format!(
-                    r#"{}\.hangman-internal-savefile.toml"#,
-                    std::env::var("HOMEPATH").unwrap()
-                )),
-                PathBuf::from(format!(
-                    r#"{}\.hangman.log"#,
-                    std::env::var("HOMEPATH").unwrap()
-                )),
-            )
+fn handle_show(args: ShowArgs, config: &Config, store: &dyn SaveStore) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+    savefile.ensure_active()?;
+    let asks_reveal = savefile.status == GameStatus::InProgress && args.reveal;
+    if asks_reveal && !args.i_give_up && JSON_OUTPUT.load(Ordering::Relaxed) {
+        bail!("Revealing the word with --json can't ask for confirmation, pass --i-give-up");
+    }
+    let reveal =
+        asks_reveal && (args.i_give_up || confirm("Reveal the word of the current game?")?);
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        return print_json(&GameJson::new(&savefile, config.matching(), reveal));
+    }
+    let style = MaskStyle {
+        mask_char: args.mask_char.unwrap_or(config.mask_char),
+        case: if args.upper {
+            DisplayCase::Upper
         } else {
-            (
-                PathBuf::from(format!(
-                    "{}/.config/hangman_current_game.toml",
-                    std::env::var("HOME").unwrap()
-                )),
-                PathBuf::from(format!(
-                    "{}/.config/hangman.log",
-                    std::env::var("HOME").unwrap()
-                )),
-            )
-        };
-        Config {
-            wordlist: None,
-            savefile: Some(savefile),
-            logfile: Some(logfile),
-            strikes: 8,
+            config.display_case
+        },
+    };
+    if args.compact {
+        println!("{}", render_compact(&savefile, config.matching(), style));
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        render_game(
+            &savefile,
+            config.matching(),
+            style,
+            !args.no_alphabet,
+            &Terminal,
+        )
+    );
+    println!("Guesses: {}", savefile.guessed.len());
+    if let Some(player) = savefile
+        .current_player()
+        .filter(|_| savefile.status == GameStatus::InProgress)
+    {
+        println!("Turn: {}", player.name);
+    }
+    if config.free_hints > 0 {
+        println!(
+            "Free hints left: {}",
+            config.free_hints.saturating_sub(savefile.hints_used)
+        );
+    }
+    if args.progress || config.show_progress {
+        println!("{}", render_progress(&savefile, config.matching()));
+    }
+    if args.frequencies {
+        println!(
+            "{}",
+            render_frequencies(&savefile, config.matching(), &Terminal)
+        );
+    }
+    if let Some(elapsed) = savefile.elapsed() {
+        println!("Time: {}", format_elapsed(elapsed));
+    }
+    match savefile.status {
+        GameStatus::InProgress => {
+            if reveal {
+                println!("Revealed word: {}", savefile.word);
+            }
         }
+        GameStatus::Won if informational_output() => {
+            println!("This game has been won, start a new one with `new`")
+        }
+        GameStatus::Lost if informational_output() => println!(
+            "This game has been lost, the word was {}. Start a new one with `new`",
+            savefile.word
+        ),
+        GameStatus::Lost => println!("The word was {}", savefile.word),
+        GameStatus::Won => {}
     }
+    Ok(())
 }
 
-impl Provider for Config {
-    fn metadata(&self) -> figment::Metadata {
-        figment::Metadata::named("Default config")
+/// Plays until the game is over or stdin ends
+///
+/// The game is saved to `interrupt_store` when playing is interrupted with
+/// Ctrl-C, as the handler outlives the borrow of `store`
+fn handle_play(
+    autosave: bool,
+    config: &Config,
+    words: &dyn WordSource,
+    store: &mut dyn SaveStore,
+    mut interrupt_store: Box<dyn SaveStore + Send>,
+    statsfile_path: PathBuf,
+    leaderboard_path: PathBuf,
+) -> anyhow::Result<()> {
+    let mut savefile = store.load()?;
+    if savefile.word.is_empty() || savefile.status != GameStatus::InProgress {
+        info!("No game in progress, starting a new one");
+        handle_new(NewArgs::default(), config, words, store, &statsfile_path)?;
+        savefile = store.load()?;
     }
 
-    fn data(
-        &self,
-    ) -> Result<Map<Profile, Dict>, Error> {
-        let wordlist_conv = match &self.wordlist {
-            None => "None",
-            Some(pathbuf) => pathbuf.to_str().unwrap(),
-        };
-        let savefile_conv = match &self.savefile {
-            None => "None",
-            Some(pathbuf) => pathbuf.to_str().unwrap(),
-        };
-        let logfile_conv = match &self.logfile {
-            None => "None",
-            Some(pathbuf) => pathbuf.to_str().unwrap(),
-        };
-        let mut dict = Dict::new();
-        dict.insert("wordlist".to_string(), Value::from(wordlist_conv));
-        dict.insert("savefile".to_string(), Value::from(savefile_conv));
-        dict.insert("logfile".to_string(), Value::from(logfile_conv));
-        dict.insert("strikes".to_string(), Value::from(self.strikes));
-        Ok(figment::value::Map::from([(
-            Profile::Default,
-            dict,
-        )]))
+    // Without autosave the game only lives in memory while playing, so save it when interrupted
+    let game = Arc::new(Mutex::new(savefile));
+    {
+        let game = Arc::clone(&game);
+        ctrlc::set_handler(move || {
+            if let Ok(savefile) = game.lock() {
+                if let Err(err) = interrupt_store.save(&savefile) {
+                    eprintln!("Error: {:#}", err);
+                }
+            }
+            println!();
+            std::process::exit(130);
+        })
+        .context("Failed to set the Ctrl-C handler")?;
     }
-}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct Savefile {
-    pub(crate) word: String,
-    pub(crate) guessed: Vec<char>,
-    pub(crate) correct: Vec<char>,
-    pub(crate) incorrect: Vec<char>,
-    pub(crate) strikes_left: u8,
-}
+    loop {
+        {
+            let savefile = game.lock().expect("Game lock was poisoned");
+            println!(
+                "{}",
+                render_game(
+                    &savefile,
+                    config.matching(),
+                    config.mask_style(),
+                    true,
+                    &Terminal
+                )
+            );
+            if savefile.status != GameStatus::InProgress {
+                break;
+            }
+        }
+
+        match game
+            .lock()
+            .expect("Game lock was poisoned")
+            .current_player()
+        {
+            Some(player) => print!("{}, guess a letter: ", player.name),
+            None => print!("Guess a letter: "),
+        }
+        std::io::stdout()
+            .flush()
+            .context("Failed to flush stdout")?;
+        let mut input = String::new();
+        if std::io::stdin()
+            .read_line(&mut input)
+            .context("Failed to read guess from stdin")?
+            == 0
+        {
+            println!();
+            break;
+        }
+        let guess = normalize_guess(input.trim(), config.matching());
+        let [character] = guess[..] else {
+            println!("Please enter a single letter");
+            continue;
+        };
 
-impl Default for Savefile {
-    fn default() -> Self {
-        Self {
-            word: "".to_string(),
-            guessed: vec![],
-            correct: vec![],
-            incorrect: vec![],
-            strikes_left: 8,
+        let mut savefile = game.lock().expect("Game lock was poisoned");
+        if apply_guess(&mut savefile, character, config.matching()) {
+            let correct = savefile.correct.contains(&character);
+            savefile.record_turn(correct);
+        } else {
+            println!("'{}' has already been guessed", character);
+        }
+        announce_status(&mut savefile, config.matching());
+        if autosave {
+            store.save(&savefile)?;
         }
     }
+
+    let savefile = game.lock().expect("Game lock was poisoned").clone();
+    finish_turn(
+        &savefile,
+        store,
+        &statsfile_path,
+        &leaderboard_path,
+        config.scoring(),
+    )
 }
 
-impl Provider for Savefile {
-    fn metadata(&self) -> figment::Metadata {
-        figment::Metadata::named("Default savefile")
+/// Replays the guess history of the current game, hints aren't in the history
+/// so their letters and strikes are left out
+fn handle_replay(speed: u64, config: &Config, store: &dyn SaveStore) -> anyhow::Result<()> {
+    let savefile = store.load()?;
+    savefile.ensure_active()?;
+    if savefile.history.is_empty() {
+        bail!("No guesses have been made yet, there is nothing to replay");
     }
 
-    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
-        let mut dict = Dict::new();
-        dict.insert("word".to_string(), Value::from(self.word.clone()));
-        dict.insert("guessed".to_string(), Value::from(self.guessed.clone()));
-        dict.insert("correct".to_string(), Value::from(self.correct.clone()));
-        dict.insert("incorrect".to_string(), Value::from(self.incorrect.clone()));
-        dict.insert("strikes_left".to_string(), Value::from(self.strikes_left));
-        Ok(figment::value::Map::from([(
-            Profile::Default,
-            dict,
-        )]))
+    let mut replay = Savefile {
+        word: savefile.word.clone(),
+        strikes_left: savefile.strikes,
+        strikes: savefile.strikes,
+        ..Savefile::default()
+    };
+    println!(
+        "{}",
+        render_game(
+            &replay,
+            config.matching(),
+            config.mask_style(),
+            true,
+            &Terminal
+        )
+    );
+    for (_, character, _) in &savefile.history {
+        std::thread::sleep(Duration::from_millis(speed));
+        if !apply_guess(&mut replay, *character, config.matching()) {
+            penalize_repeat(&mut replay, *character);
+        }
+        println!();
+        println!("Guessed '{}'", character);
+        println!(
+            "{}",
+            render_game(
+                &replay,
+                config.matching(),
+                config.mask_style(),
+                true,
+                &Terminal
+            )
+        );
     }
+    announce_status(&mut replay, config.matching());
+    Ok(())
 }
 
-fn handle_guess(guess: String) {
-    println!("Guessing: {}", guess);
+/// The analysis of a wordlist, as printed by `wordlist-stats`
+#[derive(Debug, Serialize)]
+struct WordlistStats {
+    /// Every line that isn't blank or a `#` comment
+    entries: usize,
+    /// The entries that can be picked as a word
+    usable: usize,
+    /// The entries left out for having characters other than letters, spaces, apostrophes and hyphens
+    non_alphabetic: usize,
+    /// How many usable words have each amount of letters
+    lengths: BTreeMap<usize, usize>,
+    /// The letters appearing most often in the usable words, most common first
+    common_letters: Vec<(char, usize)>,
 }
 
-fn handle_query(check: Option<String>) {
-    match check {
-        Some(check) => println!("Checking: {}", check),
-        None => println!("Querying all"),
-    }
-}
+/// How many of the most common letters `wordlist-stats` shows
+const COMMON_LETTERS: usize = 5;
 
-//noinspection SpellCheckingInspection
-fn handle_new(file: Option<PathBuf>, savefile_path: PathBuf) {
-    //noinspection SpellCheckingInspection
-    let mut random_word: String;
+fn handle_wordlist_stats(file: PathBuf) -> anyhow::Result<()> {
+    let wordlist = read_wordlist(&file)?.nfc().collect::<String>();
+    let entries = wordlist
+        .lines()
+        .map(collapse_spaces)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect::<Vec<String>>();
+    let usable = entries
+        .iter()
+        .filter(|entry| is_usable_word(entry))
+        .collect::<Vec<&String>>();
 
-    if let Some(file_path) = file {
-        info!("Starting new game with wordfile: {:?}", file_path);
-        if !file_path.exists() {
-            error!("Wordlist file does not exist, exiting");
-            std::process::exit(1);
-        } else if file_path.is_dir() {
-            error!("Given wordlist is a directory, exiting");
-            std::process::exit(1);
-        } else {
-            let wordlist = std::fs::read_to_string(file_path)
-                .unwrap()
-                .lines()
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>();
-            random_word = wordlist[thread_rng().gen_range(0..wordlist.len())].to_string();
-            debug!(
-                "Successfully generated random word from file: {}",
-                random_word
-            );
+    let mut lengths = BTreeMap::new();
+    let mut letters = BTreeMap::new();
+    for word in &usable {
+        *lengths.entry(letter_count(word)).or_insert(0) += 1;
+        for letter in word.to_lowercase().chars().filter(|c| c.is_alphabetic()) {
+            *letters.entry(letter).or_insert(0) += 1;
         }
-    } else {
-        let api_response = reqwest::blocking::get("https://random-word-api.vercel.app/api?words=1")
-            .expect("Failed to get random word from api!");
-        random_word = api_response.text().unwrap();
-        debug!(
-            "Successfully generated random word from API: {}",
-            random_word
-        );
     }
+    let mut common_letters = letters.into_iter().collect::<Vec<(char, usize)>>();
+    common_letters.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    common_letters.truncate(COMMON_LETTERS);
+    let stats = WordlistStats {
+        entries: entries.len(),
+        usable: usable.len(),
+        non_alphabetic: entries.len() - usable.len(),
+        lengths,
+        common_letters,
+    };
 
-    random_word = random_word
-        .trim_matches(|x| x == '[' || x == ']' || x == '"')
-        .parse()
-        .unwrap();
-
-    // Load the existing savefile
-    let mut savefile: Savefile = Figment::new()
-        .merge(Toml::file(&savefile_path))
-        .extract()
-        .expect("Failed to load savefile");
-
-    // Update the word field
-    savefile.word = random_word.clone();
-    let mut file = std::fs::File::create(&savefile_path).expect("Failed to create new savefile");
-    file.write_all(
-        toml::to_string(&Savefile {
-            word: random_word,
-            guessed: vec![],
-            correct: vec![],
-            incorrect: vec![],
-            strikes_left: 8,
-        })
-        .expect("Failed to serialize savefile")
-        .as_bytes(),
-    )
-    .unwrap();
+    if JSON_OUTPUT.load(Ordering::Relaxed) {
+        return print_json(&stats);
+    }
+    println!("{:<32}{:>8}", "Entries", stats.entries);
+    println!("{:<32}{:>8}", "Usable words", stats.usable);
+    println!(
+        "{:<32}{:>8}",
+        "With unusable characters", stats.non_alphabetic
+    );
+    for (length, count) in &stats.lengths {
+        let letters = if *length == 1 { "letter" } else { "letters" };
+        println!(
+            "{:<32}{:>8}",
+            format!("Words of {} {}", length, letters),
+            count
+        );
+    }
+    println!(
+        "{:<32}{:>8}",
+        "Most common letters",
+        stats
+            .common_letters
+            .iter()
+            .map(|(letter, _)| letter.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    Ok(())
 }
 
-fn verify_toml_file(file: &PathBuf) -> bool {
-    file.exists() && file.is_file() && file.extension() == Some("toml".as_ref())
+fn handle_stats(statsfile_path: PathBuf) -> anyhow::Result<()> {
+    let stats = load_stats(&statsfile_path)?;
+    println!("{:<32}{:>8}", "Games won", stats.games_won);
+    println!("{:<32}{:>8}", "Games lost", stats.games_lost);
+    println!("{:<32}{:>8}", "Total guesses", stats.total_guesses);
+    println!(
+        "{:<32}{:>8.2}",
+        "Average strikes left on wins",
+        stats.average_strikes_left_on_wins()
+    );
+    println!("{:<32}{:>8}", "Best score", stats.best_score);
+    println!("{:<32}{:>8.2}", "Average score", stats.average_score());
+    Ok(())
 }
 
-fn handle_save(file: PathBuf) {
-    println!("Saving game to file: {:?}", file);
+fn handle_leaderboard(top: usize, leaderboard_path: PathBuf) -> anyhow::Result<()> {
+    let leaderboard = load_leaderboard(&leaderboard_path)?;
+    if leaderboard.entries.is_empty() {
+        println!("No games have been won yet");
+        return Ok(());
+    }
+    println!(
+        "{:<4}{:<20}{:<20}{:>8}{:>8}",
+        "#", "Name", "Word", "Score", "Strikes"
+    );
+    for (rank, entry) in leaderboard.entries.iter().take(top).enumerate() {
+        println!(
+            "{:<4}{:<20}{:<20}{:>8}{:>8}",
+            rank + 1,
+            entry.name.as_deref().unwrap_or("-"),
+            entry.word,
+            entry.score,
+            entry.strikes_left
+        );
+    }
+    Ok(())
 }
 
-fn handle_load(file: PathBuf) {
-    println!("Loading game from file: {:?}", file);
-}
+/// Example values of the options that are unset by default, written by `config init`
+const OPTIONAL_CONFIG_EXAMPLES: [(&str, &str); 5] = [
+    (
+        "wordlist",
+        "[\"/path/to/words.txt\", \"/path/to/more-words.txt.gz\"]",
+    ),
+    ("categories", "\"/path/to/categories\""),
+    ("difficulty", "\"medium\""),
+    ("max_word_length", "10"),
+    ("wordlist_url", "\"https://example.com/words.txt\""),
+];
 
-fn handle_show() {
-    println!("Showing current game");
+fn handle_config(
+    action: ConfigCommands,
+    config: &Config,
+    figment: &Figment,
+    config_path: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    match action {
+        ConfigCommands::Show => {
+            let table = toml::Table::try_from(config).context("Failed to serialize the config")?;
+            for (key, value) in table {
+                let source = match figment.find_metadata(&key) {
+                    Some(metadata) => match &metadata.source {
+                        Some(source) => format!("{} {}", metadata.name, source),
+                        None => metadata.name.to_string(),
+                    },
+                    None => "unknown".to_string(),
+                };
+                println!("{} = {} # from {}", key, value, source);
+            }
+        }
+        ConfigCommands::Path => match config_path {
+            Some(path) => println!("{}", path.display()),
+            None => println!(
+                "No configuration file is used, pass one with --config or set HANGMAN_PROFILE or HANGMAN_CONFIG"
+            ),
+        },
+        ConfigCommands::Init { path, force } => {
+            let path = path.unwrap_or_else(|| config_dir().join("hangman.toml"));
+            if path.exists() && !force {
+                bail!("{:?} already exists, pass --force to overwrite it", path);
+            }
+            let default_config = Config::default();
+            let table =
+                toml::Table::try_from(&default_config).context("Failed to serialize the config")?;
+            let mut contents = String::from(
+                "# The hangman configuration, every value is the default\n# Uncomment a line to change it\n",
+            );
+            for (key, value) in table {
+                contents.push_str(&format!("# {} = {}\n", key, value));
+            }
+            contents.push_str("\n# Unset by default, uncomment a line to set it\n");
+            for (key, example) in OPTIONAL_CONFIG_EXAMPLES {
+                contents.push_str(&format!("# {} = {}\n", key, example));
+            }
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory {:?}", parent))?;
+            }
+            write_atomic(&path, contents.as_bytes())?;
+            println!(
+                "Wrote the default configuration to {:?}, use it with --config or HANGMAN_CONFIG",
+                path
+            );
+        }
+    }
+    Ok(())
 }
 
-fn handle_completions(directory: Option<PathBuf>) {
-    match directory {
-        Some(directory) => println!("Generating completions for directory: {:?}", directory),
-        None => println!("Generating completions for current directory"),
+fn handle_completions(
+    directory: Option<PathBuf>,
+    shell: Option<Shell>,
+    stdout: bool,
+) -> anyhow::Result<()> {
+    let mut command = Cli::command();
+    let bin_name = command.get_name().to_string();
+    if let Some(shell) = shell.filter(|_| stdout) {
+        clap_complete::generate(shell, &mut command, &bin_name, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    let directory = directory.unwrap_or(PathBuf::from("."));
+    if !directory.is_dir() {
+        bail!("Given directory {:?} does not exist", directory);
+    }
+
+    let shells = match shell {
+        Some(shell) => vec![shell],
+        None => vec![
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::PowerShell,
+            Shell::Elvish,
+        ],
+    };
+    for shell in shells {
+        let path = directory.join(shell.file_name(&bin_name));
+        let mut file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create completion file {:?}", path))?;
+        clap_complete::generate(shell, &mut command, &bin_name, &mut file);
+        info!("Generated {} completions at {:?}", shell, path);
     }
+    Ok(())
 }
 
 fn init_logger(debug: u8) -> Result<(), fern::InitError> {
@@ -302,7 +2177,7 @@ fn init_logger(debug: u8) -> Result<(), fern::InitError> {
         .debug(Color::Green)
         .trace(Color::Magenta);
 
-    Dispatch::new()
+    let console = Dispatch::new()
         .format(move |out, message, record| {
             out.finish(format_args!(
                 "{}[{}][{}] {}",
@@ -312,38 +2187,188 @@ fn init_logger(debug: u8) -> Result<(), fern::InitError> {
                 message
             ))
         })
+        .chain(std::io::stderr());
+    let logfile = Dispatch::new()
+        .format(|out, message, record| {
+            out.finish(format_args!(
+                "{}[{}][{}] {}",
+                Local::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                record.level(),
+                record.target(),
+                message
+            ))
+        })
+        .chain(fern::Output::call(|record| {
+            let mut file = LOG_FILE.lock().expect("Logfile lock was poisoned");
+            if let Some(file) = file.as_mut() {
+                let _ = writeln!(file, "{}", record.args());
+            }
+        }));
+
+    Dispatch::new()
         .level(level)
-        .chain(std::io::stdout())
+        .chain(console)
+        .chain(logfile)
         .apply()?;
     Ok(())
 }
 
+/// The logfile the logger writes to, opened once the configuration is loaded
+static LOG_FILE: Mutex<Option<std::fs::File>> = Mutex::new(None);
+
+/// Starts writing the logs to the logfile, anything logged before is only shown on the console
+fn open_logfile(logfile_path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = logfile_path.parent() {
+        std::fs::create_dir_all(parent).context("Failed to create logfile directory")?;
+    }
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(logfile_path)
+        .with_context(|| format!("Failed to open logfile {:?}", logfile_path))?;
+    *LOG_FILE.lock().expect("Logfile lock was poisoned") = Some(file);
+    debug!("Writing logs to {:?}", logfile_path);
+    Ok(())
+}
+
+/// Moves the logfile to `<logfile>.1` once it is larger than the maximum size,
+/// replacing the previous rotated logfile
+fn rotate_logfile(logfile_path: &Path, max_bytes: u64) -> anyhow::Result<()> {
+    if max_bytes == 0 {
+        return Ok(());
+    }
+    let Ok(metadata) = std::fs::metadata(logfile_path) else {
+        return Ok(());
+    };
+    if metadata.len() <= max_bytes {
+        return Ok(());
+    }
+
+    let mut rotated = logfile_path.as_os_str().to_owned();
+    rotated.push(".1");
+    warn!(
+        "Logfile {:?} is {} bytes, more than the maximum of {}, rotating it",
+        logfile_path,
+        metadata.len(),
+        max_bytes
+    );
+    std::fs::rename(logfile_path, &rotated)
+        .with_context(|| format!("Failed to rotate logfile {:?}", logfile_path))
+}
+
+/// Resolves a path the way `canonicalize` does, also for files that don't exist yet
+fn resolved_path(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    match (parent.canonicalize(), path.file_name()) {
+        (Ok(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Fails if two of the named files are the same, as writing one would corrupt the other
+fn check_distinct_files(files: &[(&str, Option<&Path>)]) -> anyhow::Result<()> {
+    let files = files
+        .iter()
+        .filter_map(|(name, path)| path.map(|path| (*name, resolved_path(path))))
+        .collect::<Vec<(&str, PathBuf)>>();
+    for (i, (name, path)) in files.iter().enumerate() {
+        if let Some((other, _)) = files[i + 1..].iter().find(|(_, other)| other == path) {
+            bail!(
+                "The {} and the {} are both {:?}, they must be different files",
+                name,
+                other,
+                path
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The exit code when a guess wins the game
+const EXIT_WON: i32 = 3;
+/// The exit code when a guess loses the game
+const EXIT_LOST: i32 = 4;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  The command succeeded
+  1  The command failed
+  2  The arguments are invalid
+  3  The guess won the game
+  4  The guess lost the game";
+
 fn main() {
+    match run() {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("Error: {:#}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs the command, returning the exit code
+fn run() -> anyhow::Result<i32> {
     let cli = Cli::parse();
     // Initialize the logger
-    if let Err(e) = init_logger(cli.debug) {
+    if let Err(e) = init_logger(if cli.quiet { 0 } else { cli.debug }) {
         eprintln!("Failed to initialize logger: {:?}", e);
         std::process::exit(1);
     }
     debug!("Successfully initialized logger");
 
+    let no_color_env = std::env::var("NO_COLOR").is_ok_and(|value| !value.is_empty());
+    if cli.no_color || no_color_env {
+        debug!("Disabling colored output");
+        COLOR_OUTPUT.store(false, Ordering::Relaxed);
+    }
+    if cli.json {
+        debug!("Printing the game as JSON");
+        JSON_OUTPUT.store(true, Ordering::Relaxed);
+    }
+    if cli.quiet {
+        QUIET_OUTPUT.store(true, Ordering::Relaxed);
+    }
+
     // Load configuration file if provided
     let mut using_default_config = false;
-    let mut figment: Figment = Figment::new().merge(Config::default());
+    let mut config_path = None;
+    let default_config = Config::default();
+    let mut figment: Figment = Figment::new().merge(default_config.clone());
     if let Some(config) = cli.config {
         // Handle the configuration file
         debug!("Loading configuration file: {:?}", config);
         if verify_toml_file(&config) {
             info!(
-                "Provided configuration file, {} is a valid TOML file",
-                config.to_str().unwrap().to_string()
+                "Provided configuration file, {:?} is a valid TOML file",
+                config
             );
-            figment = Figment::new().merge(Toml::file(config));
+            figment = figment.merge(Toml::file(&config));
+            config_path = Some(config);
         } else {
             error!(
                 "Configuration file provided is not a valid TOML file, trying HANGMAN_CONFIG next"
             );
         }
+    } else if let Ok(profile) = std::env::var("HANGMAN_PROFILE") {
+        let path = profile_path(&profile);
+        info!("HANGMAN_PROFILE, {} is set. Using {:?}", profile, path);
+        if verify_toml_file(&path) {
+            figment = figment.merge(Toml::file(&path));
+            config_path = Some(path);
+        } else {
+            error!(
+                "Profile {} has no valid TOML configuration file at {:?}",
+                profile, path
+            );
+            debug!("Using default configuration");
+            using_default_config = true;
+        }
     } else {
         let env_config = std::env::var("HANGMAN_CONFIG");
         match env_config {
@@ -352,7 +2377,8 @@ fn main() {
                 let path = PathBuf::from(file.clone());
                 if verify_toml_file(&path) {
                     info!("HANGMAN_CONFIG, {} is a valid TOML file", &file);
-                    figment = Figment::new().merge(Toml::file(path));
+                    figment = figment.merge(Toml::file(&path));
+                    config_path = Some(path);
                 } else {
                     error!("HANGMAN_CONFIG, {} is not a valid TOML file", file);
                     error!("Tip! If not using HANGMAN_CONFIG, unset the variable using your shell's `unset` function");
@@ -370,56 +2396,529 @@ fn main() {
     if using_default_config {
         info!("Loading default internal configuration");
     }
+    if let Some(profile) = &cli.profile {
+        let Some(path) = &config_path else {
+            bail!(
+                "--profile needs a configuration file holding a [profile.{}] section",
+                profile
+            );
+        };
+        let key = format!("profile.{}", profile);
+        let file = Figment::from(Toml::file(path));
+        if file.find_value(&key).is_err() {
+            bail!(
+                "Configuration file {:?} has no [profile.{}] section",
+                path,
+                profile
+            );
+        }
+        info!("Using profile {} of {:?}", profile, path);
+        figment = figment.merge(file.focus(&key));
+    }
 
-    let savefile: PathBuf = figment
-        .extract::<Config>()
-        .expect("Failed to extract configuration")
+    let config: Config = figment
+        .extract()
+        .context("Failed to extract configuration")?;
+    if !(1..=MAX_STRIKES).contains(&config.strikes) {
+        bail!(
+            "The configured strikes must be between 1 and {}, got {}",
+            MAX_STRIKES,
+            config.strikes
+        );
+    }
+    if !(1..=config.strikes).contains(&config.word_guess_penalty) {
+        bail!(
+            "The configured word_guess_penalty must be between 1 and the {} strikes, got {}",
+            config.strikes,
+            config.word_guess_penalty
+        );
+    }
+    if config.use_symbols {
+        debug!("Marking guesses with symbols");
+        SYMBOL_OUTPUT.store(true, Ordering::Relaxed);
+    }
+    let mut savefile: PathBuf = config
         .savefile
-        .unwrap_or(Config::default().savefile.unwrap());
+        .clone()
+        .or(default_config.savefile.clone())
+        .context("No savefile is configured")?;
+    if config.savefile == default_config.savefile {
+        // The default savefile is named after its format, so `load --file` detects it
+        savefile.set_extension(config.format.extension());
+    }
+    let statsfile: PathBuf = config
+        .statsfile
+        .clone()
+        .or(default_config.statsfile)
+        .context("No statsfile is configured")?;
+    let leaderboard: PathBuf = config
+        .leaderboard
+        .clone()
+        .or(default_config.leaderboard)
+        .context("No leaderboard is configured")?;
+    let logfile = config.logfile.clone().or(default_config.logfile);
+    check_distinct_files(&[
+        ("configuration file", config_path.as_deref()),
+        ("savefile", Some(&savefile)),
+        ("logfile", logfile.as_deref()),
+        ("statsfile", Some(&statsfile)),
+        ("leaderboard", Some(&leaderboard)),
+    ])?;
+    if let Some(logfile) = &logfile {
+        rotate_logfile(logfile, config.log_max_bytes)?;
+        open_logfile(logfile)?;
+    }
     debug!("Current received savefile: {:?}", savefile);
-    info!("Savefile does not exist, creating new savefile");
+    let mut store = FileStore {
+        path: savefile.clone(),
+        format: config.format,
+    };
     if !savefile.exists() {
-        info!("Savefile does not exist, creating new savefile");
-        if let Some(parent) = savefile.parent() {
-            std::fs::create_dir_all(parent).expect("Failed to create savefile directory");
-        }
-        let mut file = std::fs::File::create(&savefile).expect("Failed to create new savefile");
-        file.write_all(
-            toml::to_string(&Savefile::default())
-                .expect("Failed to serialize savefile")
-                .as_bytes(),
-        )
-        .expect("Failed to write savefile");
+        if cli.subcommands.reads_savefile() {
+            if cli.no_create {
+                bail!(
+                    "Savefile {:?} does not exist and --no-create was given, start a game with `hangman new`",
+                    savefile
+                );
+            }
+            if !cli.subcommands.creates_savefile() {
+                debug!("Not creating the savefile for a command that only reads it");
+                bail!("No game is active, start one with `hangman new`");
+            }
+            info!("Savefile does not exist, creating new savefile");
+            if let Some(parent) = savefile.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create savefile directory")?;
+            }
+            store.save(&Savefile {
+                strikes_left: config.strikes,
+                strikes: config.strikes,
+                ..Savefile::default()
+            })?;
+        } else if cli.subcommands.writes_savefile() {
+            if let Some(parent) = savefile.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create savefile directory")?;
+            }
+        }
+    }
+
+    if (cli.backup || config.backup) && cli.subcommands.mutates_savefile() && savefile.exists() {
+        backup_savefile(&savefile)?;
+    }
+
+    if config.animate_loss || matches!(cli.subcommands, Commands::Guess { animate: true, .. }) {
+        *LOSS_ANIMATION.lock().expect("Animation lock was poisoned") = Some((
+            Duration::from_millis(config.animate_delay_ms),
+            config.mask_style(),
+        ));
     }
 
+    let api_words = ApiWordSource { config: &config };
+    let words: &dyn WordSource = if cli.no_network {
+        debug!("Not using the network, taking words from the offline wordlist");
+        &OfflineWordSource
+    } else {
+        &api_words
+    };
+
     match cli.subcommands {
-        Commands::Guess { guess } => {
+        Commands::Guess {
+            file: Some(file),
+            strict,
+            ..
+        } => {
+            debug!("Running the handler for guessing from a file");
+            let status = handle_guess_file(
+                file,
+                strict || config.strict,
+                &config,
+                &mut store,
+                statsfile,
+                leaderboard,
+            )?;
+            match status {
+                GameStatus::Won => return Ok(EXIT_WON),
+                GameStatus::Lost => return Ok(EXIT_LOST),
+                GameStatus::InProgress => {}
+            }
+        }
+        Commands::Guess {
+            word: Some(word), ..
+        } => {
+            debug!("Running the handler for guessing the whole word");
+            let status = handle_guess_word(word, &config, &mut store, statsfile, leaderboard)?;
+            match status {
+                GameStatus::Won => return Ok(EXIT_WON),
+                GameStatus::Lost => return Ok(EXIT_LOST),
+                GameStatus::InProgress => {}
+            }
+        }
+        Commands::Guess {
+            guess: Some(guess),
+            eliminate: true,
+            ..
+        } => {
+            debug!("Running the handler for eliminating guesses");
+            handle_eliminate(guess, config.matching(), &mut store)?;
+        }
+        Commands::Guess {
+            guess: Some(guess),
+            strict,
+            ..
+        } => {
             debug!("Running the handler for guess function");
-            handle_guess(guess);
+            let status = handle_guess(
+                guess,
+                strict || config.strict,
+                &config,
+                &mut store,
+                statsfile,
+                leaderboard,
+            )?;
+            match status {
+                GameStatus::Won => return Ok(EXIT_WON),
+                GameStatus::Lost => return Ok(EXIT_LOST),
+                GameStatus::InProgress => {}
+            }
         }
-        Commands::Query { check } => {
+        Commands::Guess { .. } => {
+            unreachable!("clap requires --guess unless --word or --file is given")
+        }
+        Commands::Query { check, count } => {
             debug!("Running the handler for query function");
-            handle_query(check);
+            handle_query(check, count, config.matching(), &store)?;
         }
-        Commands::New { file } => {
+        Commands::New(args) => {
             debug!("Running the handler for new function");
-            handle_new(file, savefile);
+            handle_new(args, &config, words, &mut store, &statsfile)?;
         }
-        Commands::Save { file } => {
+        Commands::Save { file, slot, force } => {
             debug!("Running the handler for save function");
-            handle_save(file);
+            let file = resolve_save_target(file, slot, &savefile, config.format)?;
+            handle_save(file, force, &store, config.format)?;
         }
-        Commands::Load { file } => {
+        Commands::Load { file, slot, verify } => {
             debug!("Running the handler for load function");
-            handle_load(file);
+            let is_slot = slot.is_some();
+            let file = resolve_save_target(file, slot, &savefile, config.format)?;
+            if is_slot && !file.exists() {
+                bail!("There is no saved slot at {:?}", file);
+            }
+            if verify {
+                if !handle_verify(file) {
+                    return Ok(1);
+                }
+            } else {
+                handle_load(file, &mut store)?;
+            }
+        }
+        Commands::Reset { yes } => {
+            debug!("Running the handler for reset function");
+            handle_reset(yes, config.strikes, &mut store)?;
+        }
+        Commands::Categories => {
+            debug!("Running the handler for categories function");
+            handle_categories(config.categories)?;
         }
-        Commands::Show => {
+        Commands::Slots => {
+            debug!("Running the handler for slots function");
+            handle_slots(savefile)?;
+        }
+        Commands::Show(args) => {
             debug!("Running the handler for show function");
-            handle_show();
+            handle_show(args, &config, &store)?;
+        }
+        Commands::Hint { count: true, .. } => {
+            debug!("Running the handler for counting the letters left");
+            handle_hint_count(config.matching(), &store)?;
+        }
+        Commands::Hint { free, .. } => {
+            debug!("Running the handler for hint function");
+            handle_hint(free, &config, &mut store, statsfile, leaderboard)?;
+        }
+        Commands::Play { autosave } => {
+            debug!("Running the handler for play function");
+            let interrupt_store = Box::new(store.clone());
+            handle_play(
+                autosave,
+                &config,
+                words,
+                &mut store,
+                interrupt_store,
+                statsfile,
+                leaderboard,
+            )?;
+        }
+        Commands::Stats => {
+            debug!("Running the handler for stats function");
+            handle_stats(statsfile)?;
+        }
+        Commands::History { since } => {
+            debug!("Running the handler for history function");
+            handle_history(since, &store)?;
+        }
+        Commands::Leaderboard { top } => {
+            debug!("Running the handler for leaderboard function");
+            handle_leaderboard(top, leaderboard)?;
+        }
+        Commands::Replay { speed } => {
+            debug!("Running the handler for replay function");
+            handle_replay(speed, &config, &store)?;
         }
-        Commands::Completions { directory } => {
+        Commands::Undo => {
+            debug!("Running the handler for undo function");
+            handle_undo(&mut store)?;
+        }
+        Commands::Completions {
+            directory,
+            shell,
+            stdout,
+        } => {
             debug!("Running the handler for completions function");
-            handle_completions(directory);
+            handle_completions(directory, shell, stdout)?;
+        }
+        Commands::Cache { action } => {
+            debug!("Running the handler for cache function");
+            let cache = config
+                .word_cache
+                .clone()
+                .or(default_config.word_cache)
+                .context("No word cache is configured")?;
+            handle_cache(action, &config, cache, cli.no_network)?;
+        }
+        Commands::Config { action } => {
+            debug!("Running the handler for config function");
+            handle_config(action, &config, &figment, config_path)?;
+        }
+        Commands::WordlistStats { file } => {
+            debug!("Running the handler for wordlist stats function");
+            handle_wordlist_stats(file)?;
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hangman_rs::MemoryStore;
+
+    /// A store holding a game of the word that was just started
+    fn game_store(word: &str) -> MemoryStore {
+        MemoryStore {
+            savefile: Savefile {
+                word: word.to_string(),
+                ..Savefile::default()
+            },
+        }
+    }
+
+    #[test]
+    fn guess_show_and_query_run_on_a_memory_store() {
+        let config = Config::default();
+        let mut store = game_store("apple");
+        let unused = std::env::temp_dir().join("hangman-test-unused");
+
+        let status = handle_guess(
+            "apz".to_string(),
+            false,
+            &config,
+            &mut store,
+            unused.join("stats.toml"),
+            unused.join("leaderboard.toml"),
+        )
+        .unwrap();
+        assert_eq!(status, GameStatus::InProgress);
+        assert_eq!(store.savefile.guessed, vec!['a', 'p', 'z']);
+        assert_eq!(store.savefile.correct, vec!['a', 'p']);
+        assert_eq!(store.savefile.incorrect, vec!['z']);
+        assert_eq!(store.savefile.strikes_left, 7);
+
+        handle_show(ShowArgs::default(), &config, &store).unwrap();
+        handle_query(Some("az".to_string()), false, config.matching(), &store).unwrap();
+        handle_query(None, true, config.matching(), &store).unwrap();
+        assert_eq!(store.savefile.guessed, vec!['a', 'p', 'z']);
+        assert!(!unused.exists());
+    }
+
+    /// Always gives the same word, so `new` can be tested without the word API
+    struct FixedWordSource(&'static str);
+
+    impl WordSource for FixedWordSource {
+        fn fetch_word(&self, _language: &str, accepts: &dyn Fn(&str) -> bool) -> Option<String> {
+            Some(self.0.to_string()).filter(|word| accepts(word))
+        }
+
+        fn describe(&self) -> String {
+            "a fixed word".to_string()
+        }
+    }
+
+    /// The default configuration without the files that could hold words from earlier runs
+    fn test_config() -> Config {
+        Config {
+            word_cache: None,
+            wordlist_url_cache: None,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn new_takes_the_word_of_an_injected_source() {
+        let config = test_config();
+        let mut store = MemoryStore::default();
+        let stats = std::env::temp_dir().join("hangman-test-unused-stats.toml");
+
+        handle_new(
+            NewArgs::default(),
+            &config,
+            &FixedWordSource("kiwi"),
+            &mut store,
+            &stats,
+        )
+        .unwrap();
+        assert_eq!(store.savefile.word, "kiwi");
+        assert_eq!(store.savefile.strikes_left, config.strikes);
+        assert_eq!(store.savefile.status, GameStatus::InProgress);
+    }
+
+    #[test]
+    fn new_refuses_fewer_strikes_than_the_word_guess_penalty() {
+        let config = Config {
+            word_guess_penalty: 3,
+            ..test_config()
+        };
+        let mut store = MemoryStore::default();
+        let stats = std::env::temp_dir().join("hangman-test-unused-stats.toml");
+        let args = NewArgs {
+            strikes: Some(2),
+            ..NewArgs::default()
+        };
+
+        assert!(handle_new(args, &config, &FixedWordSource("kiwi"), &mut store, &stats).is_err());
+        assert!(!store.savefile.is_active());
+    }
+
+    #[test]
+    fn no_network_skips_the_wordlist_url() {
+        let config = Config {
+            wordlist_url: Some("http://127.0.0.1:9/words.txt".to_string()),
+            ..test_config()
+        };
+        assert!(url_wordlist("http://127.0.0.1:9/words.txt", &config, true)
+            .unwrap()
+            .is_none());
+
+        let mut store = MemoryStore::default();
+        let stats = std::env::temp_dir().join("hangman-test-unused-stats.toml");
+        handle_new(
+            NewArgs::default(),
+            &config,
+            &OfflineWordSource,
+            &mut store,
+            &stats,
+        )
+        .unwrap();
+        let first = usable_words(offline_wordlist(DEFAULT_LANGUAGE), |_| true)[0].clone();
+        assert_eq!(store.savefile.word, first);
+    }
+
+    #[test]
+    fn no_network_refuses_to_refill_the_cache() {
+        let cache = std::env::temp_dir().join("hangman-test-unused-cache.txt");
+        let refill = CacheCommands::Refill { count: 1 };
+        assert!(handle_cache(refill, &test_config(), cache.clone(), true).is_err());
+        assert!(!cache.exists());
+    }
+
+    /// An empty directory of its own for a test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("hangman-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn verify_toml_file_ignores_the_case_of_the_extension() {
+        let dir = test_dir("toml-case");
+        for name in ["config.toml", "config.TOML", "config.Toml"] {
+            std::fs::write(dir.join(name), "strikes = 6\n").unwrap();
+            assert!(
+                verify_toml_file(&dir.join(name)),
+                "{} should be accepted",
+                name
+            );
         }
+        std::fs::write(dir.join("config.json"), "{}").unwrap();
+        assert!(!verify_toml_file(&dir.join("config.json")));
+        assert!(!verify_toml_file(&dir.join("missing.toml")));
+        std::fs::create_dir(dir.join("directory.toml")).unwrap();
+        assert!(!verify_toml_file(&dir.join("directory.toml")));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn verify_toml_file_follows_symlinks() {
+        let dir = test_dir("toml-symlink");
+        let target = dir.join("real.toml");
+        std::fs::write(&target, "strikes = 6\n").unwrap();
+        std::os::unix::fs::symlink(&target, dir.join("link.toml")).unwrap();
+        std::os::unix::fs::symlink(dir.join("gone.toml"), dir.join("dangling.toml")).unwrap();
+
+        assert!(verify_toml_file(&dir.join("link.toml")));
+        assert!(!verify_toml_file(&dir.join("dangling.toml")));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn wrong_words_cost_the_word_guess_penalty() {
+        let config = Config {
+            word_guess_penalty: 3,
+            ..test_config()
+        };
+        let mut store = game_store("apple");
+        let unused = std::env::temp_dir().join("hangman-test-unused");
+
+        let status = handle_guess_word(
+            "apply".to_string(),
+            &config,
+            &mut store,
+            unused.join("stats.toml"),
+            unused.join("leaderboard.toml"),
+        )
+        .unwrap();
+        assert_eq!(status, GameStatus::InProgress);
+        assert_eq!(store.savefile.strikes_left, 5);
+        assert!(!unused.exists());
+    }
+
+    #[test]
+    fn handlers_refuse_a_memory_store_without_a_game() {
+        let config = Config::default();
+        let store = MemoryStore::default();
+
+        assert!(handle_show(ShowArgs::default(), &config, &store).is_err());
+        assert!(handle_query(None, false, config.matching(), &store).is_err());
+    }
+
+    #[test]
+    fn only_commands_changing_the_game_create_the_savefile() {
+        let creates = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["hangman"], args].concat()).unwrap();
+            cli.subcommands.creates_savefile()
+        };
+        assert!(creates(&["guess", "--guess", "a"]));
+        assert!(creates(&["hint"]));
+        assert!(creates(&["undo"]));
+        assert!(creates(&["play"]));
+        assert!(!creates(&["show"]));
+        assert!(!creates(&["query", "--count"]));
+        assert!(!creates(&["history"]));
+        assert!(!creates(&["replay"]));
+        assert!(!creates(&["save", "--file", "game.toml"]));
+        assert!(!creates(&["hint", "--count"]));
+        assert!(!creates(&["completions", "--shell", "bash", "--stdout"]));
     }
 }